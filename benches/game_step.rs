@@ -0,0 +1,98 @@
+//! Plays a headless game with the most balls the game allows and prints how long a game step and a rebuild of the collision grid take.
+
+use std::time::{Duration, Instant};
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use bevy_brick_breaker::game::InGameState;
+use bevy_brick_breaker::game::ball::{Ball, MAX_NUMBER_OF_BALLS, spawn_ball};
+use bevy_brick_breaker::game::broad_phase::{CollisionGrid, update_collision_grid};
+use bevy_brick_breaker::game::paddle::PADDLE_HEIGHT;
+use bevy_brick_breaker::game::replay::GAME_STEP_DURATION;
+use bevy_brick_breaker::game::resources::{BallSize, BallSpeed, BrickGhost};
+use bevy_brick_breaker::game::textures::Textures;
+use bevy_brick_breaker::headless::{HEADLESS_TIMESTEP, get_headless_app};
+use bevy_brick_breaker::launch_options::LaunchOptions;
+use bevy_brick_breaker::{WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT};
+
+const SEED: u64 = 0;
+/// Loading the level takes a few frames, a game that does not start by then is broken.
+const MAX_START_FRAMES: usize = 600;
+const BENCHMARK_FRAMES: usize = 1000;
+/// The widest angle off straight up that topped up balls are sent at, in radians.
+const MAX_LAUNCH_ANGLE: f32 = 1.;
+
+fn main() {
+    let launch_options = LaunchOptions {
+        headless: true,
+        frames: Some(u32::MAX),
+        seed: Some(SEED),
+        ..default()
+    };
+    let mut app = get_headless_app(launch_options, None);
+    app.finish();
+    app.cleanup();
+
+    let mut start_frames = 0;
+    while !is_playing(&app) {
+        if start_frames == MAX_START_FRAMES {
+            eprintln!("The game did not start within {} frames.", MAX_START_FRAMES);
+            std::process::exit(1);
+        }
+        app.update();
+        start_frames += 1;
+    }
+
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut frames = 0;
+    let mut frame_time = Duration::ZERO;
+    while frames < BENCHMARK_FRAMES && is_playing(&app) {
+        // Lost balls are replaced outside of the measurement, so every step moves the most balls.
+        top_up_balls(&mut app.world, &mut rng);
+        let start = Instant::now();
+        app.update();
+        frame_time += start.elapsed();
+        frames += 1;
+    }
+
+    let mut rebuild = IntoSystem::into_system(update_collision_grid);
+    rebuild.initialize(&mut app.world);
+    let start = Instant::now();
+    for _ in 0..BENCHMARK_FRAMES {
+        rebuild.run((), &mut app.world);
+    }
+    let rebuild_time = start.elapsed();
+
+    let steps = frames as u32 * (HEADLESS_TIMESTEP.as_nanos() / GAME_STEP_DURATION.as_nanos()) as u32;
+    let colliders = app.world.resource::<CollisionGrid>().get_entries().len();
+    println!("{} balls, {} colliders, {} steps", MAX_NUMBER_OF_BALLS, colliders, steps);
+    println!("Game step: {:.4} ms", frame_time.as_secs_f64() * 1000. / steps.max(1) as f64);
+    println!("Collision grid rebuild: {:.4} ms", rebuild_time.as_secs_f64() * 1000. / BENCHMARK_FRAMES as f64);
+}
+
+fn is_playing(app: &App) -> bool {
+    *app.world.resource::<State<InGameState>>().get() == InGameState::Play
+}
+
+/// Sends new balls up from just above the paddle until the most balls are in play.
+fn top_up_balls(world: &mut World, rng: &mut StdRng) {
+    let number_of_balls = world.query_filtered::<(), With<Ball>>().iter(world).count();
+    let balls: Vec<(Vec2, Vec3)> = (number_of_balls..MAX_NUMBER_OF_BALLS)
+        .map(|_| {
+            let position = Vec2::new(rng.gen::<f32>() * WINDOW_USABLE_WORLD_WIDTH, PADDLE_HEIGHT * 2. + rng.gen::<f32>() * WINDOW_WORLD_HEIGHT / 4.);
+            let angle = (rng.gen::<f32>() * 2. - 1.) * MAX_LAUNCH_ANGLE;
+            (position, Vec3::new(angle.sin(), angle.cos(), 0.))
+        })
+        .collect();
+
+    world.run_system_once(move |mut commands: Commands, textures: Textures, ball_speed: Res<BallSpeed>, ball_size: Res<BallSize>, brick_ghost: Res<BrickGhost>| {
+        for (position, direction) in balls.iter() {
+            let ball = Ball {
+                direction: *direction,
+                ..Ball::new(&ball_speed, &ball_size, &brick_ghost)
+            };
+            spawn_ball(&mut commands, &textures, *position, ball);
+        }
+    });
+}
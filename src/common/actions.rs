@@ -0,0 +1,249 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::common::settings::Settings;
+use crate::common::shortcuts::is_fixed_key;
+
+/// How far a stick has to be pushed before it counts, worn sticks rarely rest exactly at the center.
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+/// Everything the player can do with the keyboard or a gamepad, independent of the key it is bound to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Launch,
+    Pause,
+    Confirm,
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Launch,
+        Action::Pause,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Launch => "Launch",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::Back => "Back",
+        }
+    }
+
+    /// The gamepad button doing the same as the bound key.
+    pub fn get_gamepad_button(&self) -> GamepadButtonType {
+        match self {
+            Action::MoveLeft => GamepadButtonType::DPadLeft,
+            Action::MoveRight => GamepadButtonType::DPadRight,
+            Action::Launch => GamepadButtonType::South,
+            Action::Pause => GamepadButtonType::Start,
+            Action::Confirm => GamepadButtonType::South,
+            Action::Back => GamepadButtonType::East,
+        }
+    }
+
+    /// Gameplay and menu actions are never used at the same time, so they may share keys.
+    fn is_gameplay(&self) -> bool {
+        matches!(self, Action::MoveLeft | Action::MoveRight | Action::Launch | Action::Pause)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub launch: KeyCode,
+    pub pause: KeyCode,
+    pub confirm: KeyCode,
+    pub back: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_left: KeyCode::Left,
+            move_right: KeyCode::Right,
+            launch: KeyCode::Space,
+            pause: KeyCode::Escape,
+            confirm: KeyCode::Return,
+            back: KeyCode::Escape,
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> KeyCode {
+        match action {
+            Action::MoveLeft => self.move_left,
+            Action::MoveRight => self.move_right,
+            Action::Launch => self.launch,
+            Action::Pause => self.pause,
+            Action::Confirm => self.confirm,
+            Action::Back => self.back,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut KeyCode {
+        match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Launch => &mut self.launch,
+            Action::Pause => &mut self.pause,
+            Action::Confirm => &mut self.confirm,
+            Action::Back => &mut self.back,
+        }
+    }
+
+    /// Binds the key to the action. An action used at the same time that already had the key gets the previous key of this one instead.
+    pub fn set(&mut self, action: Action, key_code: KeyCode) {
+        let previous_key_code = self.get(action);
+        for other_action in Action::ALL {
+            if other_action != action && other_action.is_gameplay() == action.is_gameplay() && self.get(other_action) == key_code {
+                *self.get_mut(other_action) = previous_key_code;
+            }
+        }
+        *self.get_mut(action) = key_code;
+    }
+}
+
+/// What presses a button from the keyboard: either a fixed key or whichever key is bound to an action.
+#[derive(Clone, Copy)]
+pub enum Shortcut {
+    Key(KeyCode),
+    Action(Action),
+}
+
+impl Shortcut {
+    pub fn get_key_code(&self, key_bindings: &KeyBindings) -> KeyCode {
+        match self {
+            Shortcut::Key(key_code) => *key_code,
+            Shortcut::Action(action) => key_bindings.get(*action),
+        }
+    }
+}
+
+impl From<KeyCode> for Shortcut {
+    fn from(key_code: KeyCode) -> Self {
+        Shortcut::Key(key_code)
+    }
+}
+
+impl From<Action> for Shortcut {
+    fn from(action: Action) -> Self {
+        Shortcut::Action(action)
+    }
+}
+
+/// The keyboard seen through the current key bindings, together with the matching buttons of every gamepad.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    keyboard_input: Res<'w, Input<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_input: Res<'w, Input<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    settings: Res<'w, Settings>,
+}
+
+impl ActionInput<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keyboard_input.pressed(self.settings.key_bindings.get(action))
+            || self.gamepad_pressed(action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.keyboard_input.just_pressed(self.settings.key_bindings.get(action))
+            || self.gamepad_just_pressed(action)
+    }
+
+    pub fn gamepad_pressed(&self, action: Action) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.gamepad_input.pressed(GamepadButton::new(gamepad, action.get_gamepad_button()))
+        })
+    }
+
+    pub fn gamepad_just_pressed(&self, action: Action) -> bool {
+        self.gamepads.iter().any(|gamepad| {
+            self.gamepad_input.just_pressed(GamepadButton::new(gamepad, action.get_gamepad_button()))
+        })
+    }
+
+    pub fn gamepad_button_just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads.iter().any(|gamepad| self.gamepad_input.just_pressed(GamepadButton::new(gamepad, button_type)))
+    }
+
+    /// The axis of the gamepad pushed the furthest, rescaled so it starts at 0 just outside of the dead zone.
+    pub fn get_gamepad_axis(&self, axis_type: GamepadAxisType) -> f32 {
+        let value = self.gamepads.iter()
+            .filter_map(|gamepad| self.gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.);
+
+        if value.abs() < STICK_DEAD_ZONE {
+            return 0.;
+        }
+        value.signum() * (value.abs() - STICK_DEAD_ZONE) / (1. - STICK_DEAD_ZONE)
+    }
+}
+
+/// Whether binding the action to the key would also press a button of some screen.
+/// The default keys stay allowed, the replay controls share them with the gameplay actions.
+pub fn is_reserved_key(action: Action, key_code: KeyCode) -> bool {
+    KeyBindings::default().get(action) != key_code && is_fixed_key(key_code)
+}
+
+/// A short name of the key for labels and hints.
+pub fn get_key_name(key_code: KeyCode) -> String {
+    let name = match key_code {
+        KeyCode::Key0 => "0",
+        KeyCode::Key1 => "1",
+        KeyCode::Key2 => "2",
+        KeyCode::Key3 => "3",
+        KeyCode::Key4 => "4",
+        KeyCode::Key5 => "5",
+        KeyCode::Key6 => "6",
+        KeyCode::Key7 => "7",
+        KeyCode::Key8 => "8",
+        KeyCode::Key9 => "9",
+        KeyCode::Escape => "Esc",
+        KeyCode::Return => "Enter",
+        KeyCode::Back => "Backspace",
+        KeyCode::ShiftLeft => "Left Shift",
+        KeyCode::ShiftRight => "Right Shift",
+        KeyCode::ControlLeft => "Left Ctrl",
+        KeyCode::ControlRight => "Right Ctrl",
+        KeyCode::AltLeft => "Left Alt",
+        KeyCode::AltRight => "Right Alt",
+        _ => return format!("{:?}", key_code),
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserves_the_keys_of_every_screen() {
+        assert!(is_reserved_key(Action::Launch, KeyCode::M));
+        assert!(is_reserved_key(Action::Launch, KeyCode::S));
+        assert!(is_reserved_key(Action::Pause, KeyCode::Key1));
+        assert!(!is_reserved_key(Action::Launch, KeyCode::A));
+    }
+
+    #[test]
+    fn allows_the_default_keys() {
+        for action in Action::ALL {
+            assert!(!is_reserved_key(action, KeyBindings::default().get(action)));
+        }
+    }
+}
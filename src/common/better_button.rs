@@ -1,17 +1,30 @@
 use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use crate::common::actions::{Action, ActionInput, Shortcut, get_key_name};
+use crate::common::settings::Settings;
+use crate::AppState;
 
 const DEFAULT_NORMAL_BUTTON: Color = Color::WHITE;
 const DEFAULT_HOVERED_BUTTON: Color = Color::rgb(0.8,0.8,0.8);
 const DEFAULT_PRESSED_BUTTON: Color = Color::rgb(0.6,0.6,0.6);
+/// How far the stick has to be pushed to move the focus to the next button.
+const STICK_NAVIGATION_THRESHOLD: f32 = 0.5;
 
 pub struct BetterButtonPlugin;
 
 impl Plugin for BetterButtonPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, (
+        app.init_resource::<ButtonFocus>()
+        .add_systems(PreUpdate, (
             update_color_buttons,
-            (update_release_buttons, update_release_buttons_with_force_key).chain()
-        ));
+            (
+                update_release_buttons,
+                update_release_buttons_with_force_key,
+                reset_button_focus.run_if(state_changed::<AppState>()),
+                update_button_focus,
+            ).chain()
+        ))
+        .add_systems(PostUpdate, update_button_labels.before(UiSystem::Layout));
     }
 }
 
@@ -23,7 +36,27 @@ pub struct ReleaseButton {
 
 #[derive(Component)]
 pub struct ReleaseButtonForceKey {
-    pub key_code: KeyCode,
+    pub shortcut: Shortcut,
+}
+
+/// Marks the button a gamepad would press, see `ButtonFocus`.
+#[derive(Component)]
+pub struct FocusedButton;
+
+/// Lets a gamepad move through the buttons on screen, from top to bottom and left to right.
+///
+/// The focus is kept as a position rather than an entity, so it stays in place when a screen is rebuilt.
+/// A new screen starts with its first button focused, so a gamepad can press it right away.
+#[derive(Resource, Default)]
+pub struct ButtonFocus {
+    index: Option<usize>,
+    previous_stick_direction: i32,
+}
+
+/// The text of a button, shown together with the key that presses it.
+#[derive(Component)]
+pub struct ButtonLabel {
+    pub label: String,
 }
 
 #[derive(Component)]
@@ -43,9 +76,9 @@ impl Default for ReleaseButton {
 }
 
 impl ReleaseButtonForceKey {
-    pub fn new(key_code: KeyCode) -> Self {
+    pub fn new(shortcut: impl Into<Shortcut>) -> Self {
         Self {
-            key_code
+            shortcut: shortcut.into()
         }
     }
 }
@@ -83,37 +116,126 @@ fn update_release_buttons(
 fn update_release_buttons_with_force_key(
     mut button_query: Query<(&mut ReleaseButton, &ReleaseButtonForceKey)>,
     input: Res<Input<KeyCode>>,
+    action_input: ActionInput,
+    settings: Res<Settings>,
 )
 {
     for (mut button, key) in button_query.iter_mut() {
-        if input.just_pressed(key.key_code) {
+        if input.just_pressed(key.shortcut.get_key_code(&settings.key_bindings)) {
             button.just_released = true;
         }
+
+        // Confirm on a gamepad presses the focused button instead.
+        if let Shortcut::Action(action) = key.shortcut {
+            if action != Action::Confirm && action_input.gamepad_just_pressed(action) {
+                button.just_released = true;
+            }
+        }
     }
 }
 
+/// Sends the focus back to the first button, for screens replacing the previous one.
+pub fn reset_button_focus(
+    mut focus: ResMut<ButtonFocus>,
+)
+{
+    focus.index = None;
+}
+
+fn update_button_focus(
+    mut commands: Commands,
+    mut focus: ResMut<ButtonFocus>,
+    action_input: ActionInput,
+    mut button_query: Query<(Entity, &GlobalTransform, &mut ReleaseButton, Option<&FocusedButton>)>,
+)
+{
+    let stick_y = action_input.get_gamepad_axis(GamepadAxisType::LeftStickY);
+    let stick_direction = if stick_y > STICK_NAVIGATION_THRESHOLD { -1 } else if stick_y < -STICK_NAVIGATION_THRESHOLD { 1 } else { 0 };
+    let mut step = 0;
+    if stick_direction != focus.previous_stick_direction {
+        step = stick_direction;
+    }
+    focus.previous_stick_direction = stick_direction;
+    if action_input.gamepad_button_just_pressed(GamepadButtonType::DPadUp) {
+        step = -1;
+    }
+    if action_input.gamepad_button_just_pressed(GamepadButtonType::DPadDown) {
+        step = 1;
+    }
+
+    let mut buttons: Vec<(Entity, Vec3)> = button_query.iter()
+        .map(|(entity, transform, _, _)| (entity, transform.translation()))
+        .collect();
+    if buttons.is_empty() {
+        return;
+    }
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let count = buttons.len() as i32;
+    focus.index = match focus.index {
+        None => Some(0),
+        Some(index) => Some((index.min(buttons.len() - 1) as i32 + step).rem_euclid(count) as usize),
+    };
+    let focused_entity = focus.index.map(|index| buttons[index].0);
+
+    let confirm = action_input.gamepad_just_pressed(Action::Confirm);
+    for (entity, _, mut button, focused) in button_query.iter_mut() {
+        let is_focused = Some(entity) == focused_entity;
+        if is_focused && focused.is_none() {
+            commands.entity(entity).insert(FocusedButton);
+        }
+        else if !is_focused && focused.is_some() {
+            commands.entity(entity).remove::<FocusedButton>();
+        }
+
+        if is_focused && confirm {
+            button.just_released = true;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn update_color_buttons(
     mut interaction_query: Query<
         (
             &ColorButton,
             &Interaction,
+            Option<&FocusedButton>,
             &mut BackgroundColor
         ),
-        (Changed<Interaction>, With<Button>)
+        With<Button>
     >,
+    changed_button_query: Query<Entity, (With<Button>, Or<(Changed<Interaction>, Added<FocusedButton>)>)>,
+    mut unfocused_buttons: RemovedComponents<FocusedButton>,
 )
 {
-    for (color_button, interaction, mut color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *color = color_button.pressed_color.into();
-            }
-            Interaction::Hovered => {
-                *color = color_button.hovered_color.into();
-            }
-            Interaction::None => {
-                *color = color_button.normal_color.into();
-            }
+    let changed_buttons: Vec<Entity> = changed_button_query.iter().chain(unfocused_buttons.read()).collect();
+    for entity in changed_buttons {
+        let Ok((color_button, interaction, focused, mut color)) = interaction_query.get_mut(entity) else {
+            continue;
+        };
+        color.0 = match (*interaction, focused) {
+            (Interaction::Pressed, _) => color_button.pressed_color,
+            (Interaction::Hovered, _) | (Interaction::None, Some(_)) => color_button.hovered_color,
+            (Interaction::None, None) => color_button.normal_color,
+        };
+    }
+}
+
+fn update_button_labels(
+    settings: Res<Settings>,
+    button_query: Query<&ReleaseButtonForceKey>,
+    mut label_query: Query<(Ref<ButtonLabel>, &Parent, &mut Text)>,
+)
+{
+    for (label, parent, mut text) in label_query.iter_mut() {
+        if !label.is_added() && !settings.is_changed() {
+            continue;
         }
+
+        text.sections[0].value = match button_query.get(parent.get()) {
+            Ok(key) => format!("{} [{}]", label.label, get_key_name(key.shortcut.get_key_code(&settings.key_bindings))),
+            Err(_) => label.label.clone(),
+        };
     }
-}
\ No newline at end of file
+}
@@ -1,2 +1,6 @@
+pub mod actions;
 pub mod better_button;
+pub mod settings;
+pub mod shortcuts;
+pub mod storage;
 pub mod styles;
\ No newline at end of file
@@ -0,0 +1,129 @@
+use bevy::audio::{GlobalVolume, VolumeLevel};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+use crate::common::actions::KeyBindings;
+use crate::common::storage::{load_data_file, save_data_file};
+use crate::WINDOW_WORLD_HEIGHT;
+
+const SETTINGS_FILE_NAME: &str = "settings.ron";
+const SETTINGS_VERSION: u32 = 1;
+pub const UI_SCALES: &[f32] = &[0.75, 1.0, 1.25, 1.5];
+const VOLUME_STEPS: u32 = 10;
+
+/// How the paddle is moved.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlScheme {
+    /// Only the keys bound to the move actions.
+    #[default]
+    Keyboard,
+    /// The paddle follows the mouse cursor or a finger dragged over the screen, the keys still work as well.
+    Pointer,
+}
+
+impl ControlScheme {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ControlScheme::Keyboard => "Keyboard",
+            ControlScheme::Pointer => "Mouse & touch",
+        }
+    }
+}
+
+/// Preferences of the player, kept in the data directory between sessions.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// Replaces the scale derived from the window height when set.
+    pub ui_scale: Option<f32>,
+    pub volume: f32,
+    pub key_bindings: KeyBindings,
+    pub control_scheme: ControlScheme,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            vsync: true,
+            ui_scale: None,
+            volume: 1.0,
+            key_bindings: KeyBindings::default(),
+            control_scheme: ControlScheme::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SettingsFile {
+    version: u32,
+    settings: Settings,
+}
+
+impl Settings {
+    /// Reads the settings from the data directory, the defaults are used when there are none or they cannot be read.
+    pub fn load() -> Self {
+        load_data_file::<SettingsFile>(SETTINGS_FILE_NAME, SETTINGS_VERSION, "settings")
+            .map(|file| file.settings)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let file = SettingsFile {
+            version: SETTINGS_VERSION,
+            settings: self.clone(),
+        };
+        save_data_file(SETTINGS_FILE_NAME, &file, "settings");
+    }
+
+    pub fn get_window_mode(&self) -> WindowMode {
+        if self.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed }
+    }
+
+    pub fn get_present_mode(&self) -> PresentMode {
+        if self.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync }
+    }
+
+    pub fn get_ui_scale(&self, window_height: f32) -> f64 {
+        self.ui_scale.unwrap_or(window_height / WINDOW_WORLD_HEIGHT) as f64
+    }
+
+    /// Goes through the fixed scales, then back to the one derived from the window.
+    pub fn cycle_ui_scale(&mut self) {
+        let next_index = match self.ui_scale {
+            None => Some(0),
+            Some(scale) => UI_SCALES.iter().position(|s| *s == scale).map(|index| index + 1),
+        };
+        self.ui_scale = next_index.and_then(|index| UI_SCALES.get(index).copied());
+    }
+
+    pub fn toggle_control_scheme(&mut self) {
+        self.control_scheme = match self.control_scheme {
+            ControlScheme::Keyboard => ControlScheme::Pointer,
+            ControlScheme::Pointer => ControlScheme::Keyboard,
+        };
+    }
+
+    /// Raises the volume step by step, then starts again from silence.
+    pub fn cycle_volume(&mut self) {
+        let step = (self.volume * VOLUME_STEPS as f32).round() as u32;
+        self.volume = ((step + 1) % (VOLUME_STEPS + 1)) as f32 / VOLUME_STEPS as f32;
+    }
+}
+
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+    mut global_volume: ResMut<GlobalVolume>,
+)
+{
+    if let Ok(mut window) = window_query.get_single_mut() {
+        window.mode = settings.get_window_mode();
+        window.present_mode = settings.get_present_mode();
+        ui_scale.0 = settings.get_ui_scale(window.height());
+    }
+    global_volume.volume = VolumeLevel::new(settings.volume);
+}
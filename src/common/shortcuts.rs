@@ -0,0 +1,68 @@
+use bevy::prelude::KeyCode;
+
+// Main menu.
+pub const HIGH_SCORES_KEY: KeyCode = KeyCode::H;
+pub const OPTIONS_KEY: KeyCode = KeyCode::O;
+pub const QUIT_KEY: KeyCode = KeyCode::Q;
+
+// Mode select.
+pub const TIME_ATTACK_KEY: KeyCode = KeyCode::T;
+pub const ADVANCING_WALL_KEY: KeyCode = KeyCode::W;
+pub const CAMPAIGN_KEY: KeyCode = KeyCode::C;
+
+// Stage select.
+pub const STAGE_BUTTON_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+// High scores.
+pub const HIGH_SCORES_MODE_KEY: KeyCode = KeyCode::N;
+
+// Options.
+pub const FULLSCREEN_KEY: KeyCode = KeyCode::F;
+pub const VSYNC_KEY: KeyCode = KeyCode::Y;
+pub const UI_SCALE_KEY: KeyCode = KeyCode::U;
+pub const VOLUME_KEY: KeyCode = KeyCode::V;
+pub const CONTROL_SCHEME_KEY: KeyCode = KeyCode::C;
+/// In the order of `Action::ALL`.
+pub const BINDING_BUTTON_KEYS: [KeyCode; 6] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6];
+
+// Pause, stage clear and summary.
+pub const CONTINUE_KEY: KeyCode = KeyCode::C;
+pub const RESTART_KEY: KeyCode = KeyCode::R;
+pub const REPLAY_KEY: KeyCode = KeyCode::P;
+pub const EXPORT_KEY: KeyCode = KeyCode::E;
+pub const MENU_KEY: KeyCode = KeyCode::M;
+pub const ERASE_NAME_KEY: KeyCode = KeyCode::Back;
+
+// Replay controls.
+pub const SEEK_BACKWARD_KEY: KeyCode = KeyCode::Left;
+pub const PLAYBACK_PAUSE_KEY: KeyCode = KeyCode::Space;
+pub const PLAYBACK_SPEED_KEY: KeyCode = KeyCode::S;
+pub const SEEK_FORWARD_KEY: KeyCode = KeyCode::Right;
+
+/// Every key above, grouped by screen.
+const FIXED_KEYS: [&[KeyCode]; 9] = [
+    &[HIGH_SCORES_KEY, OPTIONS_KEY, QUIT_KEY],
+    &[TIME_ATTACK_KEY, ADVANCING_WALL_KEY, CAMPAIGN_KEY],
+    &STAGE_BUTTON_KEYS,
+    &[HIGH_SCORES_MODE_KEY],
+    &[FULLSCREEN_KEY, VSYNC_KEY, UI_SCALE_KEY, VOLUME_KEY, CONTROL_SCHEME_KEY],
+    &BINDING_BUTTON_KEYS,
+    &[CONTINUE_KEY, RESTART_KEY, REPLAY_KEY, EXPORT_KEY, MENU_KEY],
+    &[ERASE_NAME_KEY],
+    &[SEEK_BACKWARD_KEY, PLAYBACK_PAUSE_KEY, PLAYBACK_SPEED_KEY, SEEK_FORWARD_KEY],
+];
+
+/// Whether some screen has a button pressed by the key, whatever the key bindings are.
+pub fn is_fixed_key(key_code: KeyCode) -> bool {
+    FIXED_KEYS.iter().any(|keys| keys.contains(&key_code))
+}
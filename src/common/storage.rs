@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const DATA_DIR_NAME: &str = "bevy-brick-breaker";
+/// Replaces the data directory, so tests and scripted sessions leave the player's files alone.
+pub const DATA_DIR_VARIABLE: &str = "BRICK_BREAKER_DATA_DIR";
+
+/// The directory where the game keeps files between sessions, e.g. `~/.local/share/bevy-brick-breaker` on Linux.
+pub fn get_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_VARIABLE) {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::data_dir().map(|dir| dir.join(DATA_DIR_NAME))
+}
+
+/// Only the version of a data file, readable whatever the rest of the file looks like.
+#[derive(Deserialize)]
+struct DataFileVersion {
+    version: u32,
+}
+
+/// Reads a versioned RON file from the data directory.
+///
+/// Gives `None` when the file does not exist yet, or after a warning when it cannot be read, is broken or has another version.
+/// `T` is the whole file, including its `version` field.
+pub fn load_data_file<T: DeserializeOwned>(file_name: &str, version: u32, description: &str) -> Option<T> {
+    let path = get_data_dir()?.join(file_name);
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(error) => {
+            warn!("Could not read the {} from '{}': {}", description, path.display(), error);
+            return None;
+        }
+    };
+
+    let file_version = match ron::from_str::<DataFileVersion>(&text) {
+        Ok(header) => header.version,
+        Err(error) => {
+            warn!("The {} in '{}' are corrupted ({}), using the defaults.", description, path.display(), error);
+            return None;
+        }
+    };
+    if file_version != version {
+        warn!("The {} in '{}' have the unsupported version {}, using the defaults.", description, path.display(), file_version);
+        return None;
+    }
+
+    match ron::from_str::<T>(&text) {
+        Ok(data) => Some(data),
+        Err(error) => {
+            warn!("The {} in '{}' are corrupted ({}), using the defaults.", description, path.display(), error);
+            None
+        }
+    }
+}
+
+/// Writes a RON file to the data directory, failures are only logged.
+pub fn save_data_file<T: Serialize>(file_name: &str, data: &T, description: &str) {
+    let text = ron::ser::to_string_pretty(data, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string());
+    write_data_file(file_name, text, description);
+}
+
+/// Writes a JSON file to the data directory for other programs to read, failures are only logged.
+///
+/// Gives the path of the file once it is written.
+pub fn export_json_file<T: Serialize>(file_name: &str, data: &T, description: &str) -> Option<PathBuf> {
+    let text = serde_json::to_string_pretty(data)
+        .map_err(|error| error.to_string());
+    write_data_file(file_name, text, description)
+}
+
+fn write_data_file(file_name: &str, text: Result<String, String>, description: &str) -> Option<PathBuf> {
+    let Some(dir) = get_data_dir() else {
+        warn!("Could not find a data directory, the {} will not be saved.", description);
+        return None;
+    };
+    let path = dir.join(file_name);
+
+    let result = text.and_then(|text| {
+        std::fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+        std::fs::write(&path, text).map_err(|error| error.to_string())
+    });
+
+    match result {
+        Ok(()) => Some(path),
+        Err(error) => {
+            warn!("Could not save the {} to '{}': {}", description, path.display(), error);
+            None
+        }
+    }
+}
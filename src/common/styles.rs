@@ -1,19 +1,56 @@
 use bevy::asset::AssetServer;
 use bevy::prelude::*;
-use crate::common::better_button::{ColorButton, ReleaseButton, ReleaseButtonForceKey};
+use crate::common::actions::Shortcut;
+use crate::common::better_button::{ButtonLabel, ColorButton, ReleaseButton, ReleaseButtonForceKey};
 
-pub fn spawn_full_screen_menu_button<T : Default>(
+pub fn spawn_full_screen_menu_button<T : Component + Default>(
     parent: &mut ChildBuilder,
     asset_server: &Res<AssetServer>,
     label: impl Into<String>,
-    key_code: KeyCode,
-) where (T, ReleaseButton, ReleaseButtonForceKey, ColorButton, ButtonBundle): bevy::prelude::Bundle
+    shortcut: impl Into<Shortcut>,
+)
+{
+    spawn_button(parent, asset_server, T::default(), label, shortcut, Vec2::new(190., 49.), 40.);
+}
+
+/// A button wide enough to show a setting together with its value. The marker tells the buttons of a screen apart.
+pub fn spawn_full_screen_menu_wide_button<T : Bundle>(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    marker: T,
+    label: impl Into<String>,
+    shortcut: impl Into<Shortcut>,
+)
+{
+    spawn_button(parent, asset_server, marker, label, shortcut, Vec2::new(440., 36.), 26.);
+}
+
+pub fn spawn_hud_button<T : Component + Default>(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    label: impl Into<String>,
+    shortcut: impl Into<Shortcut>,
+)
+{
+    spawn_button(parent, asset_server, T::default(), label, shortcut, Vec2::new(120., 32.), 20.);
+}
+
+/// The label gets the bound key appended once the button is in the world, see `ButtonLabel`.
+fn spawn_button<T : Bundle>(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    marker: T,
+    label: impl Into<String>,
+    shortcut: impl Into<Shortcut>,
+    size: Vec2,
+    font_size: f32,
+)
 {
     parent.spawn(
         (
-            T::default(),
+            marker,
             ReleaseButton::default(),
-            ReleaseButtonForceKey::new(key_code),
+            ReleaseButtonForceKey::new(shortcut),
             ColorButton::default(),
             ButtonBundle {
                 image: UiImage {
@@ -21,8 +58,8 @@ pub fn spawn_full_screen_menu_button<T : Default>(
                     ..default()
                 },
                 style: Style {
-                    height: Val::Px(49.),
-                    width: Val::Px(190.),
+                    height: Val::Px(size.y),
+                    width: Val::Px(size.x),
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
                     ..default()
@@ -31,15 +68,19 @@ pub fn spawn_full_screen_menu_button<T : Default>(
             }
         )
     ).with_children(|parent| {
+        let label = label.into();
         parent.spawn(
-            TextBundle::from_section(format!("{} [{:?}]", label.into(), key_code), TextStyle {
-                font: asset_server.load("fonts/OpenSans-Regular.ttf"),
-                font_size: 40.,
-                color: Color::BLACK,
-            }).with_style(Style {
-                margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(0.), Val::Px(6.)),
-                ..default()
-            })
+            (
+                TextBundle::from_section(label.clone(), TextStyle {
+                    font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                    font_size,
+                    color: Color::BLACK,
+                }).with_style(Style {
+                    margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(0.), Val::Px(0.15 * font_size)),
+                    ..default()
+                }),
+                ButtonLabel { label },
+            )
         );
     });
 }
@@ -59,6 +100,21 @@ pub fn spawn_full_screen_menu_header(
     );
 }
 
+pub fn spawn_full_screen_menu_text(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    label: impl Into<String>,
+)
+{
+    parent.spawn(
+        TextBundle::from_section(label, TextStyle {
+            font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+            font_size: 30.,
+            color: Color::WHITE,
+        })
+    );
+}
+
 pub fn get_full_screen_menu_node_bundle() -> NodeBundle {
     NodeBundle {
         style: Style {
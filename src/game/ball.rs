@@ -1,75 +1,116 @@
 use bevy::prelude::*;
+use crate::game::textures::Textures;
 use crate::{WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT};
+use crate::game::InGameState;
 use crate::game::events::LastBallDestroyed;
+use crate::game::interpolation::InterpolatedTransform;
 use crate::game::shared::xy0;
-use super::collider::BoxCollider;
-use super::resources::{BallSize, BallSpeed};
+use crate::game::trajectory::BallTrajectory;
+use super::broad_phase::CollisionGrid;
+use super::collider::{BoxCollider, SweepHit};
+use super::paddle::Paddle;
+use super::power_ups::StuckBall;
+use super::resources::{BallSize, BallSpeed, BrickGhost, Lives, PaddleDeflection};
 
 pub const BALL_SIZE: f32 = 22.0;
-const MAX_NUMBER_OF_BALLS: usize = 128;
+pub const MAX_NUMBER_OF_BALLS: usize = 128;
+/// Stops a ball wedged between obstacles from bouncing forever within a single move.
+const MAX_BOUNCES_PER_MOVE: usize = 8;
 
 #[derive(Copy, Clone)]
 pub enum BallObstacleType {
-    Ghost,
     Natural,
-    Centric,
+    /// Bounces balls like a natural obstacle, but ghost balls pass through it.
+    Breakable,
+    /// Sends the ball up at an angle depending on where it hits, see [`PaddleDeflection`].
+    Deflecting,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy)]
 pub struct Ball {
     pub direction: Vec3,
+    pub speed: BallSpeed,
+    pub size: BallSize,
+    /// Passes through breakable obstacles instead of bouncing off them, hitting them on the way.
+    pub ghost: bool,
+}
+
+impl Ball {
+    /// A ball as it is put into play, heading straight up.
+    pub fn new(ball_speed: &BallSpeed, ball_size: &BallSize, brick_ghost: &BrickGhost) -> Self {
+        Self {
+            direction: Vec3::Y,
+            speed: *ball_speed,
+            size: *ball_size,
+            ghost: brick_ghost.is_enabled(),
+        }
+    }
+
+    /// Faster balls glow warmer and ghost balls are see-through, so every ball shows what it picked up.
+    pub fn get_color(&self) -> Color {
+        let boost = self.speed.get_boost();
+        let alpha = if self.ghost { 0.45 } else { 1. };
+        Color::rgba(1., 1. - 0.5 * boost, 1. - 0.8 * boost, alpha)
+    }
 }
 
 #[derive(Component)]
 pub struct BallObstacle {
     pub obstacle_type: BallObstacleType,
-    pub hit_flag: bool,
+    /// The ball that hit the obstacle in the latest step.
+    pub hit_by: Option<Entity>,
 }
 
 impl BallObstacle {
     pub fn new(obstacle_type: BallObstacleType) -> Self {
         BallObstacle {
             obstacle_type,
-            hit_flag: false,
+            hit_by: None,
         }
     }
 }
 
 pub fn spawn_first_ball(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    textures: Textures,
+    ball_speed: Res<BallSpeed>,
     ball_size: Res<BallSize>,
+    brick_ghost: Res<BrickGhost>,
 )
 {
     spawn_ball(
         &mut commands,
-        &asset_server,
+        &textures,
         Vec2::new(WINDOW_USABLE_WORLD_WIDTH / 2.0, WINDOW_WORLD_HEIGHT / 2.0),
-        Vec2::new(0., 1.),
-        &ball_size,
-    )
+        Ball::new(&ball_speed, &ball_size, &brick_ghost),
+    );
 }
 
 pub fn spawn_ball(
     commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
+    textures: &Textures,
     position: Vec2,
-    direction: Vec2,
-    ball_size: &BallSize,
-)
+    ball: Ball,
+) -> Entity
 {
     commands.spawn((
         SpriteBundle {
             transform: Transform{
                 translation: Vec3::new(position.x, position.y, 0.),
-                scale: ball_size.get_scale3(),
+                scale: ball.size.get_scale3(),
                 ..default()
             },
-            texture: asset_server.load("sprites/ballBlue.png"),
+            sprite: Sprite {
+                color: ball.get_color(),
+                ..default()
+            },
+            texture: textures.load("sprites/ballBlue.png"),
             .. default()
         },
-        Ball { direction: Vec3::new(direction.x, direction.y, 0.) }
-    ));
+        ball,
+        BallTrajectory::default(),
+        InterpolatedTransform::default(),
+    )).id()
 }
 
 pub fn despawn_balls(
@@ -81,49 +122,96 @@ pub fn despawn_balls(
     }
 }
 
-pub fn keep_ball_synced_with_settings(
-    ball_size: Res<BallSize>,
-    mut ball_query: Query<&mut Transform, With<Ball>>
+/// Moving balls changes them every step, so the sprite is only touched when the size or the color actually differ.
+pub fn keep_ball_synced_with_state(
+    mut ball_query: Query<(&Ball, &mut Transform, &mut Sprite), Changed<Ball>>
 )
 {
-    if ball_size.is_changed() {
-        let ball_scale = ball_size.get_scale3();
+    for (ball, mut ball_transform, mut sprite) in ball_query.iter_mut() {
+        let scale = ball.size.get_scale3();
+        if ball_transform.scale != scale {
+            ball_transform.scale = scale;
+        }
 
-        for mut ball_transform in ball_query.iter_mut() {
-            ball_transform.scale = ball_scale;
+        let color = ball.get_color();
+        if sprite.color != color {
+            sprite.color = color;
         }
     }
 }
 
 pub fn move_balls(
-    mut balls_query: Query<(&mut Transform, &mut Ball)>,
-    mut obstacle_query: Query<(&Transform, &BoxCollider, &mut BallObstacle), Without<Ball>>,
+    mut commands: Commands,
+    mut balls_query: Query<(Entity, &mut Transform, &mut Ball, &mut BallTrajectory), Without<StuckBall>>,
+    mut obstacle_query: Query<(&mut BallObstacle, Option<&Paddle>), Without<Ball>>,
+    collision_grid: Res<CollisionGrid>,
     time: Res<Time>,
-    ball_speed: Res<BallSpeed>,
-    ball_size: Res<BallSize>,
+    paddle_deflection: Res<PaddleDeflection>,
 )
 {
-    let ball_speed = ball_speed.get_speed();
-    for (mut ball_transform, ball) in balls_query.iter_mut() {
-        ball_transform.translation += ball_speed * time.delta_seconds() * ball.direction;
+    let mut obstacles = Obstacles {
+        grid: &collision_grid,
+        obstacles: collision_grid.get_entries().iter()
+            .map(|entry| {
+                let (obstacle, paddle) = obstacle_query.get(entry.entity).ok()?;
+                Some(Obstacle {
+                    center: entry.center,
+                    extents: entry.extents,
+                    obstacle_type: obstacle.obstacle_type,
+                    velocity_x: paddle.map_or(0., |paddle| paddle.velocity_x),
+                    sticky: paddle.is_some_and(|paddle| paddle.sticky),
+                })
+            })
+            .collect(),
+        hits: vec![None; collision_grid.get_entries().len()],
+        candidates: Vec::new(),
+    };
+
+    for (entity, mut ball_transform, mut ball, mut trajectory) in balls_query.iter_mut() {
+        let (position, direction, caught_at) = sweep_ball(
+            entity,
+            &ball,
+            ball_transform.translation.xy(),
+            time.delta_seconds(),
+            &paddle_deflection,
+            &mut obstacles,
+            &mut trajectory,
+            time.elapsed_seconds(),
+        );
+        ball_transform.translation = Vec3::new(position.x, position.y, ball_transform.translation.z);
+        ball.direction = xy0(direction);
+        if let Some(offset_x) = caught_at {
+            commands.entity(entity).insert(StuckBall { offset_x });
+        }
     }
 
-    let ball_radius = ball_size.get_radius();
-    bounce_ball_on_obstacles(ball_radius, &mut balls_query, &mut obstacle_query);
-    bounce_ball_on_edges(ball_radius, &mut balls_query);
+    for (mut obstacle, _) in obstacle_query.iter_mut() {
+        obstacle.hit_by = None;
+    }
+    for (entry, hit_by) in collision_grid.get_entries().iter().zip(obstacles.hits) {
+        if let (Some(ball), Ok((mut obstacle, _))) = (hit_by, obstacle_query.get_mut(entry.entity)) {
+            obstacle.hit_by = Some(ball);
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn keep_destroying_balls(
     mut commands: Commands,
     mut last_ball_destroyed_events: EventWriter<LastBallDestroyed>,
-    ball_query: Query<(Entity, &Transform), With<Ball>>,
+    ball_query: Query<(Entity, &Transform, &Ball)>,
+    paddle_query: Query<(&Transform, &BoxCollider), With<Paddle>>,
+    textures: Textures,
+    ball_speed: Res<BallSpeed>,
     ball_size: Res<BallSize>,
+    brick_ghost: Res<BrickGhost>,
+    mut lives: ResMut<Lives>,
+    mut next_state: ResMut<NextState<InGameState>>,
 )
 {
-    let destruction_level = -2. * ball_size.get_radius();
     let mut balls = 0;
-    for (entity, transform) in ball_query.iter() {
-        if transform.translation.y <= destruction_level
+    for (entity, transform, ball) in ball_query.iter() {
+        if transform.translation.y <= -2. * ball.size.get_radius()
         {
             commands.entity(entity).despawn();
         }
@@ -135,29 +223,43 @@ pub fn keep_destroying_balls(
 
     if balls == 0
     {
-        last_ball_destroyed_events.send_default();
+        lives.0 = lives.0.saturating_sub(1);
+        match paddle_query.get_single() {
+            // Another life puts a fresh ball on the paddle and waits for the player to launch it.
+            Ok((paddle_transform, paddle_collider)) if lives.0 > 0 => {
+                let ball = Ball::new(&ball_speed, &ball_size, &brick_ghost);
+                let position = paddle_transform.translation.xy() + Vec2::new(0., paddle_collider.extends.y + ball.size.get_radius());
+                spawn_ball(&mut commands, &textures, position, ball);
+                next_state.set(InGameState::Preparation);
+            }
+            _ => {
+                last_ball_destroyed_events.send_default();
+            }
+        }
     }
 }
 
+/// Adds a ball flying the opposite way next to each given ball, with the same speed, size and ghost mode.
+///
+/// Returns each original ball together with its clone.
 pub fn clone_balls(
     commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    ball_query: &Query<(&Ball, &Transform)>,
-    ball_size: &BallSize,
-)
+    textures: &Textures,
+    balls: &[(Entity, Ball, Vec2)],
+) -> Vec<(Entity, Entity)>
 {
-    let mut number_of_balls = 0;
-    for _ball in ball_query.iter() {
-        number_of_balls += 1;
-    }
-
-    for (ball, ball_transform) in ball_query.iter() {
-        if number_of_balls >= MAX_NUMBER_OF_BALLS {
-            return;
-        }
-        spawn_ball(commands, asset_server, ball_transform.translation.xy(), -ball.direction.xy(), ball_size);
-        number_of_balls += 1;
-    }
+    let number_of_clones = MAX_NUMBER_OF_BALLS.saturating_sub(balls.len());
+    balls
+        .iter()
+        .take(number_of_clones)
+        .map(|(original, ball, position)| {
+            let clone = Ball {
+                direction: -ball.direction,
+                ..*ball
+            };
+            (*original, spawn_ball(commands, textures, *position, clone))
+        })
+        .collect()
 }
 
 pub fn declone_balls(
@@ -174,107 +276,140 @@ pub fn declone_balls(
     }
 }
 
-fn bounce_ball_on_obstacles(
-    ball_radius: f32,
-    balls_query: &mut Query<(&mut Transform, &mut Ball)>,
-    obstacle_query: &mut Query<(&Transform, &BoxCollider, &mut BallObstacle), Without<Ball>>
-)
-{
-    let ball_radius_squared = ball_radius * ball_radius;
-
-    for (ball_transform, mut ball) in balls_query.iter_mut() {
-        let ball_position = ball_transform.translation.xy();
-        let mut ball_flip_direction_x = false;
-        let mut ball_flip_direction_y = false;
-        let mut ball_override_direction: Option<Vec2> = None;
-
-        for (obstacle_transform, obstacle_collider, mut obstacle) in obstacle_query.iter_mut() {
-            let obstacle_position = obstacle_transform.translation.xy();
-            let obstacle_extends = obstacle_collider.extends;
-
-            let obstacle_to_ball = ball_position - obstacle_position;
-            let obstacle_to_closest = Vec2 {
-                x: obstacle_to_ball.x.clamp(-obstacle_extends.x, obstacle_extends.x),
-                y: obstacle_to_ball.y.clamp(-obstacle_extends.y, obstacle_extends.y),
-            };
-
-            let potential_collision = obstacle_position + obstacle_to_closest;
-            if (potential_collision - ball_position).length_squared() <= ball_radius_squared
-            {
-                obstacle.hit_flag = true;
+struct Obstacle {
+    center: Vec2,
+    extents: Vec2,
+    obstacle_type: BallObstacleType,
+    velocity_x: f32,
+    sticky: bool,
+}
 
-                match obstacle.obstacle_type {
-                    BallObstacleType::Ghost => { /* do nothing */ }
-                    BallObstacleType::Natural => {
-                        if ball_flip_direction_x && ball_flip_direction_y {
-                            continue;
-                        }
+/// The obstacles of one step, found through the collision grid.
+struct Obstacles<'a> {
+    grid: &'a CollisionGrid,
+    /// One for each grid entry, none for colliders that do not stop balls.
+    obstacles: Vec<Option<Obstacle>>,
+    /// One for each grid entry, the ball that hit it.
+    hits: Vec<Option<Entity>>,
+    /// Reused for every lookup in the grid.
+    candidates: Vec<usize>,
+}
 
-                        let offset = 1.;
+impl Obstacles<'_> {
+    /// Finds the obstacles a ball of the given radius may touch when moving from `start` by `motion`.
+    fn find_candidates(&mut self, start: Vec2, motion: Vec2, radius: f32) {
+        let end = start + motion;
+        self.grid.find_candidates(start.min(end) - radius, start.max(end) + radius, &mut self.candidates);
+    }
 
-                        if potential_collision.x > obstacle_position.x - obstacle_extends.x + offset
-                            && potential_collision.x < obstacle_position.x + obstacle_extends.x - offset
-                        {
-                            ball_flip_direction_y = true;
-                            continue;
-                        }
-                        if potential_collision.y > obstacle_position.y - obstacle_extends.y + offset
-                            && potential_collision.y < obstacle_position.y + obstacle_extends.y - offset
-                        {
-                            ball_flip_direction_x = true;
-                            continue;
-                        }
+    fn get_candidates(&self) -> impl Iterator<Item = (usize, &Obstacle)> {
+        self.candidates.iter()
+            .filter_map(|index| self.obstacles[*index].as_ref().map(|obstacle| (*index, obstacle)))
+    }
+}
 
-                        ball_flip_direction_y = true;
-                        ball_flip_direction_x = true;
-                    }
-                    BallObstacleType::Centric => {
-                        ball_override_direction = Some(ball_position - obstacle_position);
-                        break;
-                    }
-                };
+/// Moves the ball the whole distance, bouncing as often as it hits something on the way.
+///
+/// Every hit is found at the exact point along the way, so a fast ball or a long frame never skips a thin obstacle.
+/// Returns the new position and direction of the ball, and where it landed relative to the paddle center when a sticky paddle caught it.
+#[allow(clippy::too_many_arguments)]
+fn sweep_ball(
+    ball_entity: Entity,
+    ball: &Ball,
+    mut position: Vec2,
+    delta_seconds: f32,
+    paddle_deflection: &PaddleDeflection,
+    obstacles: &mut Obstacles,
+    trajectory: &mut BallTrajectory,
+    time: f32,
+) -> (Vec2, Vec2, Option<f32>)
+{
+    let ball_speed = ball.speed.get_speed();
+    let ball_radius = ball.size.get_radius();
+    let passes_through = |obstacle_type: BallObstacleType| ball.ghost && matches!(obstacle_type, BallObstacleType::Breakable);
+
+    let mut direction = ball.direction.xy();
+    let mut remaining_distance = ball_speed * delta_seconds;
+    for _ in 0..MAX_BOUNCES_PER_MOVE {
+        let motion = direction * remaining_distance;
+
+        obstacles.find_candidates(position, motion, ball_radius);
+        let mut first_hit = sweep_ball_on_edges(position, motion, ball_radius).map(|hit| (hit, None));
+        for (index, obstacle) in obstacles.get_candidates() {
+            if passes_through(obstacle.obstacle_type) {
+                continue;
+            }
+            let Some(hit) = BoxCollider::sweep_circle(obstacle.center, obstacle.extents, position, motion, ball_radius) else {
+                continue;
+            };
+            if first_hit.as_ref().is_none_or(|(first_hit, _)| hit.time < first_hit.time) {
+                first_hit = Some((hit, Some(index)));
             }
         }
 
-        if let Some(direction) = ball_override_direction {
-            ball.direction = xy0(direction.normalize());
-        }
-        else {
-            if ball_flip_direction_x {
-                ball.direction.x *= -1.;
-            }
-            if ball_flip_direction_y {
-                ball.direction.y *= -1.;
+        // A ghost ball hits everything it passes through until it bounces.
+        let travel = motion * first_hit.as_ref().map_or(1., |(hit, _)| hit.time);
+        for &index in obstacles.candidates.iter() {
+            let Some(obstacle) = &obstacles.obstacles[index] else {
+                continue;
+            };
+            if passes_through(obstacle.obstacle_type)
+                && (BoxCollider::overlap_circle(obstacle.center, obstacle.extents, position, ball_radius)
+                    || BoxCollider::sweep_circle(obstacle.center, obstacle.extents, position, travel, ball_radius).is_some())
+            {
+                obstacles.hits[index] = Some(ball_entity);
             }
         }
+
+        position += travel;
+        let Some((hit, obstacle)) = first_hit else {
+            break;
+        };
+        remaining_distance *= 1. - hit.time;
+        trajectory.record_bounce(time, position);
+
+        let reflected_direction = direction - 2. * direction.dot(hit.normal) * hit.normal;
+        direction = match obstacle.and_then(|index| obstacles.obstacles[index].as_ref().map(|obstacle| (index, obstacle))) {
+            Some((index, obstacle)) => {
+                obstacles.hits[index] = Some(ball_entity);
+                match obstacle.obstacle_type {
+                    // Only the top deflects, the ends still bounce naturally.
+                    BallObstacleType::Deflecting if hit.normal.y > 0. => {
+                        trajectory.clear();
+                        if obstacle.sticky {
+                            return (position, direction, Some(position.x - obstacle.center.x));
+                        }
+                        let hit_offset = (position.x - obstacle.center.x) / (obstacle.extents.x + ball_radius);
+                        paddle_deflection.get_direction(hit_offset, obstacle.velocity_x, ball_speed)
+                    }
+                    _ => reflected_direction,
+                }
+            }
+            None => reflected_direction,
+        };
     }
-}
 
+    (position, direction, None)
+}
 
-fn bounce_ball_on_edges(
+/// The ball bounces off the left, right and top edge, the bottom one is left open.
+fn sweep_ball_on_edges(
+    position: Vec2,
+    motion: Vec2,
     ball_radius: f32,
-    balls_query: &mut Query<(&mut Transform, &mut Ball)>,
-)
+) -> Option<SweepHit>
 {
     let min_x = ball_radius;
     let max_x = WINDOW_USABLE_WORLD_WIDTH - ball_radius;
     let max_y = WINDOW_WORLD_HEIGHT - ball_radius;
 
-    for (mut ball_transform, mut ball) in balls_query.iter_mut() {
-        let mut ball_position = ball_transform.translation;
-        if ball_position.x < min_x {
-            ball_position.x = min_x;
-            ball.direction.x *= -1.0;
-        } else if ball_position.x > max_x {
-            ball_position.x = max_x;
-            ball.direction.x *= -1.0;
-        }
-
-        if ball_position.y > max_y {
-            ball_position.y = max_y;
-            ball.direction.y *= -1.0;
-        }
-
-        ball_transform.translation = ball_position;
-    }
-}
\ No newline at end of file
+    let edges = [
+        (motion.x < 0., (min_x - position.x) / motion.x, Vec2::X),
+        (motion.x > 0., (max_x - position.x) / motion.x, Vec2::NEG_X),
+        (motion.y > 0., (max_y - position.y) / motion.y, Vec2::NEG_Y),
+    ];
+    edges.into_iter()
+        .filter(|(approaching, time, _)| *approaching && *time <= 1.)
+        .map(|(_, time, normal)| SweepHit { time: time.max(0.), normal })
+        .min_by(|a, b| a.time.total_cmp(&b.time))
+}
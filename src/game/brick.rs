@@ -1,40 +1,124 @@
+use std::time::Duration;
 use bevy::prelude::*;
+use crate::game::textures::Textures;
+use serde::Deserialize;
 
-use super::ball::{BallObstacle};
+use super::ball::{BallObstacle, BallObstacleType};
 use crate::{WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT};
-use crate::game::events::BrickDestroyed;
-use crate::game::resources::{BrickGhost, BrickRowSpawnCooldown};
+use crate::game::events::{BrickDestroyed, LevelCompleted};
+use crate::game::level::{CurrentLevel, EMPTY_BRICK_SYMBOL, Level, WinCondition};
+use crate::game::game_mode::{GameMode, GameOverReason};
+use crate::game::InGameState;
+use crate::game::paddle::PADDLE_HEIGHT;
+use crate::game::resources::{BrickRowSpawnCooldown, Score, WallAdvanceTimer};
 use super::collider::BoxCollider;
+use super::interpolation::InterpolatedTransform;
 
 pub const BRICK_WIDTH: f32 = 64.0;
 pub const BRICK_HALF_WIDTH: f32 = BRICK_WIDTH / 2.0;
 pub const BRICK_HEIGHT: f32 = 32.0;
 pub const BRICK_HALF_HEIGHT: f32 = BRICK_HEIGHT / 2.0;
 
-const NUMBER_OF_BRICKS_IN_ROW: i32 = (WINDOW_USABLE_WORLD_WIDTH / BRICK_WIDTH) as i32;
+pub const NUMBER_OF_BRICKS_IN_ROW: usize = (WINDOW_USABLE_WORLD_WIDTH / BRICK_WIDTH) as usize;
 const BRICK_HORIZONTAL_SPACE: f32 = WINDOW_USABLE_WORLD_WIDTH / NUMBER_OF_BRICKS_IN_ROW as f32;
 
-const START_NUMBER_OF_ROWS: i32 = 5;
-const MAX_NUMBER_OF_ROWS: i32 = 9;
-const TARGET_MIN_NUMBER_OF_BRICKS: i32 = START_NUMBER_OF_ROWS * NUMBER_OF_BRICKS_IN_ROW;
+pub const MAX_NUMBER_OF_ROWS: usize = 16;
 
+/// Each row of the advancing wall comes this much sooner than the one before.
+const WALL_ADVANCE_SPEED_UP: f32 = 0.92;
+const WALL_MIN_ADVANCE_INTERVAL: Duration = Duration::from_millis(2500);
+/// The advancing wall wins once a brick gets below this line, four bricks above the paddle.
+const DANGER_LINE_Y: f32 = PADDLE_HEIGHT + 4. * BRICK_HEIGHT;
+const DANGER_LINE_COLOR: Color = Color::rgba(1., 0.2, 0.2, 0.35);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum BrickKind {
+    /// Destroyed once its hit points run out.
+    #[default]
+    Standard,
+    /// Never destroyed, always bounces the ball (even a ghost one).
+    Steel,
+    /// Destroyed like a standard brick, but takes down every brick within its explosion radius.
+    Explosive,
+}
+
+/// Shows how far down the advancing wall may come.
 #[derive(Component)]
-pub struct Brick;
+pub struct DangerLine;
+
+#[derive(Component)]
+pub struct Brick {
+    pub kind: BrickKind,
+    pub hit_points: u32,
+    /// The `bricks` key of the level this brick was spawned from.
+    pub symbol: char,
+    in_contact: bool,
+    /// Laser shots that hit the brick since the latest damage was dealt.
+    laser_hits: u32,
+}
+
+impl Brick {
+    pub fn is_destructible(&self) -> bool {
+        self.kind != BrickKind::Steel
+    }
+
+    pub fn hit_by_laser(&mut self) {
+        self.laser_hits += 1;
+    }
+
+    fn get_obstacle_type(&self) -> BallObstacleType {
+        if self.is_destructible() {
+            BallObstacleType::Breakable
+        }
+        else {
+            BallObstacleType::Natural
+        }
+    }
+}
 
 pub fn spawn_bricks(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    brick_ghost: Res<BrickGhost>,
+    textures: Textures,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+)
+{
+    let Some(level) = current_level.get(&levels) else {
+        return;
+    };
+
+    for (row_index, row) in level.brick_rows().enumerate() {
+        spawn_row(row_index, row, level, &mut commands, &textures);
+    }
+}
+
+pub fn spawn_danger_line(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
 )
 {
-    for row_index in 0..START_NUMBER_OF_ROWS {
-        spawn_row(row_index, &mut commands, &asset_server, &brick_ghost);
+    if *game_mode != GameMode::AdvancingWall {
+        return;
     }
+
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_xyz(WINDOW_USABLE_WORLD_WIDTH / 2., DANGER_LINE_Y, -0.5),
+            sprite: Sprite {
+                color: DANGER_LINE_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_USABLE_WORLD_WIDTH, 2.)),
+                ..default()
+            },
+            ..default()
+        },
+        DangerLine,
+    ));
 }
 
+#[allow(clippy::type_complexity)]
 pub fn despawn_bricks(
     mut commands: Commands,
-    bricks_query: Query<Entity, With<Brick>>
+    bricks_query: Query<Entity, Or<(With<Brick>, With<DangerLine>)>>
 )
 {
     for brick in bricks_query.iter() {
@@ -42,69 +126,156 @@ pub fn despawn_bricks(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn destroy_bricks_on_hit(
     mut commands: Commands,
-    bricks_query: Query<(Entity, &BallObstacle, &Transform), With<Brick>>,
+    mut bricks_query: Query<(Entity, &mut Brick, &BallObstacle, &Transform, &mut Sprite, &mut Handle<Image>)>,
+    textures: Textures,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
     mut brick_destroyed_events: EventWriter<BrickDestroyed>,
 )
 {
-    for (entity, obstacle, transform) in bricks_query.iter() {
-        if obstacle.hit_flag == true {
-            commands.entity(entity).despawn();
-            brick_destroyed_events.send(BrickDestroyed { brick_position: transform.translation.xy()});
+    let Some(level) = current_level.get(&levels) else {
+        return;
+    };
+
+    // Each destroyed brick together with the ball that took it down.
+    let mut destroyed_bricks = Vec::new();
+    for (entity, mut brick, obstacle, _, mut sprite, mut texture) in bricks_query.iter_mut() {
+        // A ball usually overlaps a brick for a few frames, only the first one counts as a hit.
+        let ball_hit = obstacle.hit_by.is_some() && !brick.in_contact;
+        brick.in_contact = obstacle.hit_by.is_some();
+        let hits = u32::from(ball_hit) + std::mem::take(&mut brick.laser_hits);
+
+        if hits == 0 || !brick.is_destructible() {
+            continue;
+        }
+
+        brick.hit_points = brick.hit_points.saturating_sub(hits);
+        if brick.hit_points == 0 {
+            destroyed_bricks.push((entity, obstacle.hit_by));
+        }
+        else if let Some(level_brick) = level.bricks.get(&brick.symbol) {
+            let hits_taken = level_brick.hit_points.saturating_sub(brick.hit_points);
+            if let Some(stage) = level_brick.get_damage_stage(hits_taken) {
+                if let Some(stage_texture) = &stage.texture {
+                    *texture = textures.load(stage_texture);
+                }
+                if let Some(stage_color) = stage.color {
+                    sprite.color = get_color(stage_color);
+                }
+            }
         }
     }
-}
 
-pub fn keep_brick_synced_with_settings(
-    mut bricks_query: Query<&mut BallObstacle, With<Brick>>,
-    brick_ghost: Res<BrickGhost>,
-)
-{
-    if brick_ghost.is_changed() {
-        let obstacle_type = brick_ghost.get_obstacle_type();
+    let mut exploding_index = 0;
+    while exploding_index < destroyed_bricks.len() {
+        let (exploding_entity, ball) = destroyed_bricks[exploding_index];
+        let (_, brick, _, transform, _, _) = bricks_query.get(exploding_entity).unwrap();
+        exploding_index += 1;
+
+        if brick.kind != BrickKind::Explosive {
+            continue;
+        }
+        let Some(level_brick) = level.bricks.get(&brick.symbol) else {
+            continue;
+        };
 
-        for mut obstacle in bricks_query.iter_mut() {
-            obstacle.obstacle_type = obstacle_type;
+        let explosion_center = transform.translation.xy();
+        let explosion_radius_squared = level_brick.explosion_radius * level_brick.explosion_radius;
+        for (other_entity, other_brick, _, other_transform, _, _) in bricks_query.iter() {
+            if !other_brick.is_destructible() || destroyed_bricks.iter().any(|(entity, _)| *entity == other_entity) {
+                continue;
+            }
+
+            if (other_transform.translation.xy() - explosion_center).length_squared() <= explosion_radius_squared {
+                destroyed_bricks.push((other_entity, ball));
+            }
         }
     }
+
+    for (entity, ball) in destroyed_bricks {
+        let (_, brick, _, transform, _, _) = bricks_query.get(entity).unwrap();
+        commands.entity(entity).despawn();
+        brick_destroyed_events.send(BrickDestroyed {
+            brick_position: transform.translation.xy(),
+            brick_kind: brick.kind,
+            ball,
+        });
+    }
 }
 
 pub fn spawn_row(
-    row_index: i32,
+    row_index: usize,
+    row: &str,
+    level: &Level,
     commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    brick_ghost: &Res<BrickGhost>,
+    textures: &Textures,
 )
 {
-    let obstacle_type = brick_ghost.get_obstacle_type();
-    for x_index in 0..NUMBER_OF_BRICKS_IN_ROW {
+    for (x_index, symbol) in row.chars().enumerate() {
+        if symbol == EMPTY_BRICK_SYMBOL {
+            continue;
+        }
+        let Some(level_brick) = level.bricks.get(&symbol) else {
+            continue;
+        };
+
+        let brick = Brick {
+            kind: level_brick.kind,
+            hit_points: level_brick.hit_points,
+            symbol,
+            in_contact: false,
+            laser_hits: 0,
+        };
+        let obstacle_type = brick.get_obstacle_type();
+
         let x = x_index as f32 * BRICK_HORIZONTAL_SPACE + BRICK_HORIZONTAL_SPACE / 2.;
         let y = WINDOW_WORLD_HEIGHT - BRICK_HALF_HEIGHT - row_index as f32 * BRICK_HEIGHT;
         commands.spawn((
             SpriteBundle {
                 transform: Transform::from_xyz(x, y, 0.0),
-                texture: asset_server.load("sprites/element_blue_rectangle.png"),
+                texture: textures.load(&level_brick.texture),
+                sprite: Sprite {
+                    color: get_color(level_brick.color),
+                    ..default()
+                },
                 ..default()
             },
-            Brick {},
+            brick,
             BallObstacle::new(obstacle_type),
             BoxCollider {
                 extends: Vec2::new(BRICK_HALF_WIDTH, BRICK_HALF_HEIGHT),
-            }
+            },
+            InterpolatedTransform::default(),
         ));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn keep_spawning_bricks(
     mut commands: Commands,
-    mut brick_query: Query<&mut Transform, With<Brick>>,
-    asset_server: Res<AssetServer>,
-    brick_ghost: Res<BrickGhost>,
+    mut brick_query: Query<(&mut Transform, &Brick)>,
+    textures: Textures,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    game_mode: Res<GameMode>,
     time: Res<Time>,
     mut brick_row_spawn_cooldown: ResMut<BrickRowSpawnCooldown>
 )
 {
+    // The advancing wall brings its rows on its own schedule.
+    if *game_mode == GameMode::AdvancingWall {
+        return;
+    }
+    let Some(level) = current_level.get(&levels) else {
+        return;
+    };
+    let WinCondition::Endless { refill_row, min_bricks, max_rows } = &level.win_condition else {
+        return;
+    };
+
     brick_row_spawn_cooldown.0.tick(time.delta());
     if !brick_row_spawn_cooldown.0.finished() {
         return;
@@ -113,22 +284,114 @@ pub fn keep_spawning_bricks(
     let mut number_of_bricks = 0;
     let mut lowest_brick_y = f32::MAX;
 
-    for brick in brick_query.iter() {
-        number_of_bricks += 1;
-        lowest_brick_y = lowest_brick_y.min(brick.translation.y);
+    for (transform, brick) in brick_query.iter() {
+        if brick.is_destructible() {
+            number_of_bricks += 1;
+        }
+        lowest_brick_y = lowest_brick_y.min(transform.translation.y);
     }
 
     let lowest_row_index = (-(lowest_brick_y - WINDOW_WORLD_HEIGHT + BRICK_HALF_HEIGHT) / BRICK_HEIGHT).round() as i32;
 
-    if lowest_row_index >= MAX_NUMBER_OF_ROWS - 1 || number_of_bricks >= TARGET_MIN_NUMBER_OF_BRICKS
+    if lowest_row_index >= *max_rows as i32 - 1 || number_of_bricks >= *min_bricks
     {
         return;
     }
 
-    for mut brick in brick_query.iter_mut() {
-        brick.translation.y -= BRICK_HEIGHT;
+    push_row(&mut brick_query, refill_row, level, &mut commands, &textures);
+    brick_row_spawn_cooldown.0.reset();
+}
+
+/// Pushes in a new row whenever the wall timer runs out, however many bricks there are.
+#[allow(clippy::too_many_arguments)]
+pub fn keep_advancing_wall(
+    mut commands: Commands,
+    mut brick_query: Query<(&mut Transform, &Brick)>,
+    textures: Textures,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    game_mode: Res<GameMode>,
+    time: Res<Time>,
+    mut wall_advance_timer: ResMut<WallAdvanceTimer>,
+)
+{
+    if *game_mode != GameMode::AdvancingWall {
+        return;
+    }
+    let Some(level) = current_level.get(&levels) else {
+        return;
+    };
+    let WinCondition::Endless { refill_row, .. } = &level.win_condition else {
+        return;
+    };
+
+    wall_advance_timer.0.tick(time.delta());
+    if !wall_advance_timer.0.finished() {
+        return;
     }
 
-    spawn_row(0, &mut commands, &asset_server, &brick_ghost);
-    brick_row_spawn_cooldown.0.reset();
-}
\ No newline at end of file
+    push_row(&mut brick_query, refill_row, level, &mut commands, &textures);
+    let interval = wall_advance_timer.0.duration().mul_f32(WALL_ADVANCE_SPEED_UP).max(WALL_MIN_ADVANCE_INTERVAL);
+    wall_advance_timer.0.set_duration(interval);
+    wall_advance_timer.0.reset();
+}
+
+/// Moves every brick down by a row and fills the top row.
+fn push_row(
+    brick_query: &mut Query<(&mut Transform, &Brick)>,
+    row: &str,
+    level: &Level,
+    commands: &mut Commands,
+    textures: &Textures,
+)
+{
+    for (mut transform, _) in brick_query.iter_mut() {
+        transform.translation.y -= BRICK_HEIGHT;
+    }
+
+    spawn_row(0, row, level, commands, textures);
+}
+
+pub fn check_wall_reached_paddle(
+    mut commands: Commands,
+    brick_query: Query<&Transform, With<Brick>>,
+    game_mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<InGameState>>,
+)
+{
+    if *game_mode != GameMode::AdvancingWall {
+        return;
+    }
+
+    if brick_query.iter().any(|transform| transform.translation.y - BRICK_HALF_HEIGHT < DANGER_LINE_Y) {
+        commands.insert_resource(GameOverReason::WallReachedPaddle);
+        next_state.set(InGameState::Summary);
+    }
+}
+
+pub fn check_level_completed(
+    brick_query: Query<&Brick>,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    score: Res<Score>,
+    mut level_completed_events: EventWriter<LevelCompleted>,
+)
+{
+    let Some(level) = current_level.get(&levels) else {
+        return;
+    };
+
+    let completed = match level.win_condition {
+        WinCondition::Endless { .. } => false,
+        WinCondition::ClearAllBricks => !brick_query.iter().any(Brick::is_destructible),
+        WinCondition::ReachScore(target_score) => score.0 >= target_score,
+    };
+
+    if completed {
+        level_completed_events.send_default();
+    }
+}
+
+fn get_color((red, green, blue): (f32, f32, f32)) -> Color {
+    Color::rgb(red, green, blue)
+}
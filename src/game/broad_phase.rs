@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use crate::{WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT};
+use super::collider::BoxCollider;
+
+/// Matches the brick height, so a brick covers two cells and a ball a handful.
+const CELL_SIZE: f32 = 32.0;
+const NUMBER_OF_COLUMNS: usize = (WINDOW_USABLE_WORLD_WIDTH / CELL_SIZE) as usize;
+const NUMBER_OF_ROWS: usize = (WINDOW_WORLD_HEIGHT / CELL_SIZE) as usize + 1;
+
+#[derive(Clone, Copy)]
+pub struct GridEntry {
+    pub entity: Entity,
+    pub center: Vec2,
+    pub extents: Vec2,
+}
+
+/// Every collider sorted into a uniform grid over the playfield, so only nearby colliders need an exact test.
+///
+/// Anything outside of the playfield ends up in the border cells.
+#[derive(Resource)]
+pub struct CollisionGrid {
+    /// In query order, which is the same for a live game and its replay.
+    entries: Vec<GridEntry>,
+    /// The entries overlapping each cell, row by row from the bottom left.
+    cells: Vec<Vec<usize>>,
+}
+
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            cells: vec![Vec::new(); NUMBER_OF_COLUMNS * NUMBER_OF_ROWS],
+        }
+    }
+}
+
+impl CollisionGrid {
+    pub fn rebuild(&mut self, colliders: impl Iterator<Item = GridEntry>) {
+        self.entries.clear();
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+
+        for entry in colliders {
+            let index = self.entries.len();
+            let (min_cell, max_cell) = get_cell_range(entry.center - entry.extents, entry.center + entry.extents);
+            for row in min_cell.1..=max_cell.1 {
+                for column in min_cell.0..=max_cell.0 {
+                    self.cells[row * NUMBER_OF_COLUMNS + column].push(index);
+                }
+            }
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn get_entries(&self) -> &[GridEntry] {
+        &self.entries
+    }
+
+    /// Collects the indices of the entries that may overlap the box from `min` to `max`, each once and in entry order.
+    pub fn find_candidates(&self, min: Vec2, max: Vec2, candidates: &mut Vec<usize>) {
+        candidates.clear();
+        let (min_cell, max_cell) = get_cell_range(min, max);
+        for row in min_cell.1..=max_cell.1 {
+            for column in min_cell.0..=max_cell.0 {
+                candidates.extend_from_slice(&self.cells[row * NUMBER_OF_COLUMNS + column]);
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+    }
+}
+
+fn get_cell(position: Vec2) -> (usize, usize) {
+    let column = (position.x / CELL_SIZE).floor().clamp(0., (NUMBER_OF_COLUMNS - 1) as f32);
+    let row = (position.y / CELL_SIZE).floor().clamp(0., (NUMBER_OF_ROWS - 1) as f32);
+    (column as usize, row as usize)
+}
+
+fn get_cell_range(min: Vec2, max: Vec2) -> ((usize, usize), (usize, usize)) {
+    (get_cell(min), get_cell(max))
+}
+
+/// Rebuilds the grid from scratch. The interpolation rewrites every transform each frame, so change detection cannot tell which colliders moved,
+/// and the `game_step` benchmark shows a rebuild costs a small part of a step even with a full board.
+pub fn update_collision_grid(
+    collider_query: Query<(Entity, &Transform, &BoxCollider)>,
+    mut collision_grid: ResMut<CollisionGrid>,
+)
+{
+    collision_grid.rebuild(collider_query.iter().map(|(entity, transform, collider)| GridEntry {
+        entity,
+        center: transform.translation.xy(),
+        extents: collider.extends,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_entry(index: u32, center: Vec2, extents: Vec2) -> GridEntry {
+        GridEntry {
+            entity: Entity::from_raw(index),
+            center,
+            extents,
+        }
+    }
+
+    fn find(grid: &CollisionGrid, min: Vec2, max: Vec2) -> Vec<usize> {
+        let mut candidates = Vec::new();
+        grid.find_candidates(min, max, &mut candidates);
+        candidates
+    }
+
+    #[test]
+    fn finds_nearby_entries_once_and_in_order() {
+        let mut grid = CollisionGrid::default();
+        grid.rebuild([
+            // Spans several cells, so it would come up more than once without the dedup.
+            get_entry(0, Vec2::new(100., 100.), Vec2::new(40., 20.)),
+            get_entry(1, Vec2::new(1000., 600.), Vec2::splat(10.)),
+            get_entry(2, Vec2::new(110., 90.), Vec2::splat(5.)),
+        ].into_iter());
+
+        assert_eq!(find(&grid, Vec2::new(80., 80.), Vec2::new(120., 120.)), vec![0, 2]);
+        assert_eq!(find(&grid, Vec2::new(990., 590.), Vec2::new(1010., 610.)), vec![1]);
+        assert!(find(&grid, Vec2::new(600., 400.), Vec2::new(610., 410.)).is_empty());
+    }
+
+    #[test]
+    fn keeps_entries_outside_the_playfield_in_the_border_cells() {
+        let mut grid = CollisionGrid::default();
+        grid.rebuild([
+            get_entry(0, Vec2::new(-50., -50.), Vec2::splat(10.)),
+            get_entry(1, Vec2::new(WINDOW_USABLE_WORLD_WIDTH + 50., WINDOW_WORLD_HEIGHT + 50.), Vec2::splat(10.)),
+        ].into_iter());
+
+        assert_eq!(find(&grid, Vec2::ZERO, Vec2::splat(1.)), vec![0]);
+        let top_right = Vec2::new(WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT + CELL_SIZE);
+        assert_eq!(find(&grid, top_right - 1., top_right), vec![1]);
+    }
+
+    #[test]
+    fn rebuilding_forgets_the_previous_entries() {
+        let mut grid = CollisionGrid::default();
+        grid.rebuild([get_entry(0, Vec2::new(100., 100.), Vec2::splat(10.))].into_iter());
+        grid.rebuild([get_entry(1, Vec2::new(500., 500.), Vec2::splat(10.))].into_iter());
+
+        assert!(find(&grid, Vec2::new(90., 90.), Vec2::new(110., 110.)).is_empty());
+        assert_eq!(grid.get_entries().len(), 1);
+        assert_eq!(grid.get_entries()[0].entity, Entity::from_raw(1));
+    }
+}
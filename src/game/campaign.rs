@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::AppState;
+use crate::common::storage::{load_data_file, save_data_file};
+use crate::game::events::NextStageRequested;
+use crate::game::InGameState;
+use crate::game::game_mode::{GameMode, GameOverReason};
+use crate::game::replay::ReplayPlayback;
+use crate::game::resources::{Lives, Score};
+
+/// The levels of the campaign, played in this order.
+pub const CAMPAIGN_STAGE_PATHS: &[&str] = &[
+    "levels/campaign/stage_1.level.ron",
+    "levels/campaign/stage_2.level.ron",
+    "levels/campaign/stage_3.level.ron",
+];
+const CAMPAIGN_PROGRESS_FILE_NAME: &str = "campaign_progress.ron";
+const CAMPAIGN_PROGRESS_VERSION: u32 = 1;
+const STAGE_CLEAR_BONUS: usize = 50000;
+const LIFE_BONUS: usize = 20000;
+
+/// The score and lives a stage starts with, earned in the stages before it.
+#[derive(Clone, Copy, PartialEq)]
+pub struct CarryOver {
+    pub score: usize,
+    pub lives: usize,
+}
+
+pub fn is_last_stage(stage: usize) -> bool {
+    stage + 1 >= CAMPAIGN_STAGE_PATHS.len()
+}
+
+/// The bonus points of the latest cleared stage, they are already part of the score.
+#[derive(Resource, Default)]
+pub struct StageBonus {
+    pub clear: usize,
+    pub lives: usize,
+}
+
+/// The stage the latest cleared stage unlocked for the stage select, if it was not unlocked before.
+#[derive(Resource, Default)]
+pub struct UnlockedStage(pub Option<usize>);
+
+/// How far the campaign got on this machine.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    /// The stages that can be picked from the stage select, always at least the first one.
+    unlocked_stages: usize,
+}
+
+/// The file keeps its own version, like the high score table.
+#[derive(Serialize, Deserialize)]
+struct CampaignProgressFile {
+    version: u32,
+    progress: CampaignProgress,
+}
+
+impl Default for CampaignProgress {
+    fn default() -> Self {
+        Self {
+            unlocked_stages: 1,
+        }
+    }
+}
+
+impl CampaignProgress {
+    pub fn is_unlocked(&self, stage: usize) -> bool {
+        stage < self.unlocked_stages
+    }
+
+    /// Unlocks every stage up to the given one, returns whether that unlocked anything new.
+    pub fn unlock(&mut self, stage: usize) -> bool {
+        let unlocked_stages = (stage + 1).min(CAMPAIGN_STAGE_PATHS.len());
+        if unlocked_stages <= self.unlocked_stages {
+            return false;
+        }
+        self.unlocked_stages = unlocked_stages;
+        true
+    }
+
+    /// Reads the progress from the data directory, only the first stage is unlocked when there is none or it cannot be read.
+    pub fn load() -> Self {
+        let Some(file) = load_data_file::<CampaignProgressFile>(CAMPAIGN_PROGRESS_FILE_NAME, CAMPAIGN_PROGRESS_VERSION, "campaign progress") else {
+            return Self::default();
+        };
+
+        Self {
+            unlocked_stages: file.progress.unlocked_stages.clamp(1, CAMPAIGN_STAGE_PATHS.len()),
+        }
+    }
+
+    pub fn save(&self) {
+        let file = CampaignProgressFile {
+            version: CAMPAIGN_PROGRESS_VERSION,
+            progress: self.clone(),
+        };
+        save_data_file(CAMPAIGN_PROGRESS_FILE_NAME, &file, "campaign progress");
+    }
+}
+
+pub fn load_campaign_progress(
+    mut commands: Commands,
+)
+{
+    commands.insert_resource(CampaignProgress::load());
+}
+
+/// Gives a stage the score and lives of the stages before it.
+pub fn apply_carry_over(
+    game_mode: Res<GameMode>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+)
+{
+    if let GameMode::Campaign { carry_over: Some(carry_over), .. } = *game_mode {
+        score.0 = carry_over.score;
+        lives.0 = carry_over.lives;
+    }
+}
+
+pub fn award_stage_bonus(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    lives: Res<Lives>,
+)
+{
+    let stage_bonus = StageBonus {
+        clear: STAGE_CLEAR_BONUS,
+        lives: lives.0 * LIFE_BONUS,
+    };
+    score.0 += stage_bonus.clear + stage_bonus.lives;
+    commands.insert_resource(stage_bonus);
+}
+
+pub fn unlock_next_stage(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    mut campaign_progress: ResMut<CampaignProgress>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+)
+{
+    let GameMode::Campaign { stage, .. } = *game_mode else {
+        return;
+    };
+
+    // Watching a replay does not unlock anything a second time.
+    let unlocked = replay_playback.is_none() && campaign_progress.unlock(stage + 1);
+    if unlocked {
+        campaign_progress.save();
+    }
+    commands.insert_resource(UnlockedStage(unlocked.then_some(stage + 1)));
+}
+
+/// Moves on to the next stage with the score and lives left, or to the summary after the last one.
+pub fn check_next_stage_condition(
+    mut commands: Commands,
+    mut next_stage_requested_events: EventReader<NextStageRequested>,
+    mut game_mode: ResMut<GameMode>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_in_game_state: ResMut<NextState<InGameState>>,
+)
+{
+    if next_stage_requested_events.is_empty() {
+        return;
+    }
+    next_stage_requested_events.clear();
+
+    let GameMode::Campaign { stage, .. } = *game_mode else {
+        return;
+    };
+
+    if is_last_stage(stage) {
+        commands.insert_resource(GameOverReason::CampaignCompleted);
+        next_in_game_state.set(InGameState::Summary);
+        return;
+    }
+
+    *game_mode = GameMode::Campaign {
+        stage: stage + 1,
+        carry_over: Some(CarryOver {
+            score: score.0,
+            lives: lives.0,
+        }),
+    };
+    // A cleared stage watched as a replay carries over the same score and lives as the live one did.
+    commands.remove_resource::<ReplayPlayback>();
+    next_app_state.set(AppState::RestartInGame);
+}
@@ -1,130 +1,194 @@
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::asset::io::Reader;
 use bevy::prelude::*;
-use rand::prelude::random;
+use bevy::reflect::TypePath;
+use bevy::utils::{BoxedFuture, HashSet};
+use serde::Deserialize;
+use thiserror::Error;
+use crate::game::textures::Textures;
+use rand::Rng;
+use crate::game::brick::BrickKind;
 use crate::game::collider::BoxCollider;
+use crate::game::effects::CollectableEffect;
 use crate::game::events::{BrickDestroyed};
-use crate::game::spark::SparkBundle;
-
-const COIN_META_INDEX: usize = 0;
-
-const COLLECTABLE_METAS:  &'static [(CollectableType, CollectableMeta)] = &[
-    (CollectableType::Coin, CollectableMeta {
-        texture_path: "sprites/collectables/element_blue_square.png",
-        z_order: 0.5,
-        drop_weight: 50,
-        scale: 0.6,
-        extends: Vec2 {
-            x: 32.0,
-            y: 32.0,
-        },
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::BallClone, CollectableMeta {
-        texture_path: "sprites/collectables/ball_clone.png",
-        drop_weight: 3,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::BallDeclone, CollectableMeta {
-        texture_path: "sprites/collectables/ball_declone.png",
-        drop_weight: 3,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::BallSizeUp, CollectableMeta {
-        texture_path: "sprites/collectables/ball_size_up.png",
-        drop_weight: 1,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::BallSizeDown, CollectableMeta {
-        texture_path: "sprites/collectables/ball_size_down.png",
-        drop_weight: 1,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::BallSpeedUp, CollectableMeta {
-        texture_path: "sprites/collectables/ball_speed_up.png",
-        drop_weight: 3,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::BallSpeedDown, CollectableMeta {
-        texture_path: "sprites/collectables/ball_speed_down.png",
-        drop_weight: 3,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::GhostUp, CollectableMeta {
-        texture_path: "sprites/collectables/ghost_up.png",
-        drop_weight: 1,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::GhostDown, CollectableMeta {
-        texture_path: "sprites/collectables/ghost_down.png",
-        drop_weight: 1,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::PaddleSizeUp, CollectableMeta {
-        texture_path: "sprites/collectables/paddle_size_up.png",
-        drop_weight: 2,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::PaddleSizeDown, CollectableMeta {
-        texture_path: "sprites/collectables/paddle_size_down.png",
-        drop_weight: 2,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::PaddleSpeedUp, CollectableMeta {
-        texture_path: "sprites/collectables/paddle_speed_up.png",
-        drop_weight: 3,
-        ..CollectableMeta::default()
-    }),
-    (CollectableType::PaddleSpeedDown, CollectableMeta {
-        texture_path: "sprites/collectables/paddle_speed_down.png",
-        drop_weight: 3,
-        ..CollectableMeta::default()
-    }),
-];
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub enum CollectableType {
-    BallClone,
-    BallDeclone,
-    BallSizeUp,
-    BallSizeDown,
-    BallSpeedUp,
-    BallSpeedDown,
-    Coin,
-    GhostUp,
-    GhostDown,
-    PaddleSizeUp,
-    PaddleSizeDown,
-    PaddleSpeedUp,
-    PaddleSpeedDown,
+use crate::game::resources::GameRng;
+use crate::game::interpolation::InterpolatedTransform;
+use crate::game::spark::{Spark, SparkBundle};
+
+pub const DEFAULT_COLLECTABLES_PATH: &str = "collectables/default.collectables.ron";
+/// Every coin is an entity falling through the collision grid, a brick dropping many more would stall the game.
+const MAX_COINS_PER_BRICK: usize = 100;
+
+/// Everything destroyed bricks can drop.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct CollectableSet {
+    pub coins: CoinDrop,
+    /// Drops are picked in this order, so changing it changes which collectable a seed drops.
+    pub collectables: Vec<CollectableDefinition>,
 }
 
-#[derive(Component)]
-pub struct Collectable {
-    pub collectable_type: CollectableType,
+/// The collectable every destroyed brick drops a handful of, on top of the randomly picked one.
+#[derive(Deserialize)]
+pub struct CoinDrop {
+    pub collectable: String,
+    pub min: usize,
+    pub max: usize,
 }
 
-struct CollectableMeta<'s> {
-    pub texture_path: &'s str,
+#[derive(Deserialize)]
+pub struct CollectableDefinition {
+    pub name: String,
+    pub texture: String,
+    #[serde(default)]
     pub z_order: f32,
-    pub extends: Vec2,
+    #[serde(default = "CollectableDefinition::default_scale")]
     pub scale: f32,
+    /// Half the size of the texture, before scaling.
+    #[serde(default = "CollectableDefinition::default_extents")]
+    pub extents: (f32, f32),
+    /// How likely the collectable is picked compared to the others, 0 never picks it.
+    #[serde(default = "CollectableDefinition::default_drop_weight")]
     pub drop_weight: usize,
+    /// Applied in order when the paddle collects it.
+    pub effects: Vec<CollectableEffect>,
 }
 
-impl CollectableMeta<'_> {
-    const fn default() -> Self {
-        Self {
-            texture_path: "",
-            z_order: 0.0,
-            extends: Vec2 {
-                x: 78.,//0.5 * 148.,
-                y: 78.,//0.5 * 148.,
-            },
-            scale: 0.3,
-            drop_weight: 1,
+impl CollectableDefinition {
+    fn default_scale() -> f32 {
+        0.3
+    }
+
+    fn default_extents() -> (f32, f32) {
+        (78., 78.)
+    }
+
+    fn default_drop_weight() -> usize {
+        1
+    }
+}
+
+impl CollectableSet {
+    /// Parses a collectables file and checks that every collectable in it can be used.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, CollectableLoaderError> {
+        let collectable_set = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_bytes::<CollectableSet>(bytes)?;
+        collectable_set.validate()?;
+        Ok(collectable_set)
+    }
+
+    fn get_coin_index(&self) -> Option<usize> {
+        self.collectables
+            .iter()
+            .position(|definition| definition.name == self.coins.collectable)
+    }
+
+    fn validate(&self) -> Result<(), CollectableLoaderError> {
+        // Checked first, spawning coins relies on the range to pick a number without overflowing.
+        if self.coins.min > self.coins.max || self.coins.max > MAX_COINS_PER_BRICK {
+            return Err(CollectableLoaderError::InvalidCoinRange {
+                min: self.coins.min,
+                max: self.coins.max,
+            });
         }
+
+        let mut names = HashSet::new();
+        for definition in self.collectables.iter() {
+            if !names.insert(definition.name.as_str()) {
+                return Err(CollectableLoaderError::DuplicateName { name: definition.name.clone() });
+            }
+
+            for effect in definition.effects.iter() {
+                effect.validate().map_err(|reason| CollectableLoaderError::InvalidEffect {
+                    name: definition.name.clone(),
+                    reason,
+                })?;
+            }
+        }
+
+        if self.get_coin_index().is_none() {
+            return Err(CollectableLoaderError::UnknownCoin { name: self.coins.collectable.clone() });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CollectableLoaderError {
+    #[error("could not read the collectables file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the collectables file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("collectable '{name}' is defined more than once")]
+    DuplicateName { name: String },
+    #[error("collectable '{name}' has an invalid effect: {reason}")]
+    InvalidEffect { name: String, reason: &'static str },
+    #[error("coins drop '{name}', which is not defined in `collectables`")]
+    UnknownCoin { name: String },
+    #[error("coins drop between {min} and {max}, which needs min <= max <= {MAX_COINS_PER_BRICK}")]
+    InvalidCoinRange { min: usize, max: usize },
+}
+
+#[derive(Default)]
+pub struct CollectableSetLoader;
+
+impl AssetLoader for CollectableSetLoader {
+    type Asset = CollectableSet;
+    type Settings = ();
+    type Error = CollectableLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>>
+    {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            CollectableSet::from_bytes(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["collectables.ron"]
     }
 }
 
+#[derive(Resource)]
+pub struct CurrentCollectableSet {
+    pub path: String,
+    pub handle: Handle<CollectableSet>,
+}
+
+impl CurrentCollectableSet {
+    pub fn get<'a>(&self, collectable_sets: &'a Assets<CollectableSet>) -> Option<&'a CollectableSet> {
+        collectable_sets.get(&self.handle)
+    }
+}
+
+#[derive(Component)]
+pub struct Collectable {
+    /// Index into the collectables of the current set.
+    pub definition: usize,
+    /// The ball that broke the brick this dropped from, ball power-ups only change that ball.
+    /// Drops of bricks shot down by lasers have none, their ball power-ups change the ball nearest the paddle.
+    pub ball: Option<Entity>,
+}
+
+pub fn load_collectable_set(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+)
+{
+    commands.insert_resource(CurrentCollectableSet {
+        path: DEFAULT_COLLECTABLES_PATH.to_string(),
+        handle: asset_server.load(DEFAULT_COLLECTABLES_PATH),
+    });
+}
+
 pub fn despawn_collectables(
     mut commands: Commands,
     collectable_query: Query<Entity, With<Collectable>>
@@ -138,46 +202,64 @@ pub fn despawn_collectables(
 pub fn keep_spawning_collectables(
     mut commands: Commands,
     mut brick_destroyed_events: EventReader<BrickDestroyed>,
-    asset_server: Res<AssetServer>,
+    textures: Textures,
+    collectable_sets: Res<Assets<CollectableSet>>,
+    current_collectable_set: Res<CurrentCollectableSet>,
+    mut rng: ResMut<GameRng>,
 )
 {
-    let total_drop_weight = COLLECTABLE_METAS
+    let Some(collectable_set) = current_collectable_set.get(&collectable_sets) else {
+        brick_destroyed_events.clear();
+        return;
+    };
+    let coin_index = collectable_set.get_coin_index().unwrap();
+    let total_drop_weight = collectable_set.collectables
         .iter()
-        .fold(0, |sum, i| sum + i.1.drop_weight);
+        .fold(0, |sum, definition| sum + definition.drop_weight);
 
     for brick_destroyed_event in brick_destroyed_events.read() {
-        let number_of_coins = 2 + random::<usize>() % 7;
+        let coins = &collectable_set.coins;
+        let number_of_coins = coins.min + rng.gen::<usize>() % (coins.max - coins.min + 1);
         for _ in 0..number_of_coins {
-            spawn_collectable(&mut commands, brick_destroyed_event.brick_position, COIN_META_INDEX, &asset_server);
+            spawn_collectable(&mut commands, brick_destroyed_event.brick_position, brick_destroyed_event.ball, collectable_set, coin_index, &textures, &mut rng);
         }
 
-        let mut rand = random::<usize>() % (total_drop_weight + 1);
-        let mut meta_index = 0;
-        for potential_index in 0..COLLECTABLE_METAS.len() {
-            if COLLECTABLE_METAS[potential_index].1.drop_weight == 0 {
+        // An explosion can take down many bricks at once, so explosive bricks only drop coins.
+        if brick_destroyed_event.brick_kind == BrickKind::Explosive {
+            continue;
+        }
+
+        let mut rand = rng.gen::<usize>() % (total_drop_weight + 1);
+        let mut definition_index = 0;
+        for (potential_index, potential_definition) in collectable_set.collectables.iter().enumerate() {
+            if potential_definition.drop_weight == 0 {
                 continue;
             }
 
-            if rand < COLLECTABLE_METAS[potential_index].1.drop_weight {
-                meta_index = potential_index;
+            if rand < potential_definition.drop_weight {
+                definition_index = potential_index;
                 break;
             }
 
-            rand -= COLLECTABLE_METAS[potential_index].1.drop_weight;
+            rand -= potential_definition.drop_weight;
         }
 
-        spawn_collectable(&mut commands, brick_destroyed_event.brick_position, meta_index, &asset_server);
+        spawn_collectable(&mut commands, brick_destroyed_event.brick_position, brick_destroyed_event.ball, collectable_set, definition_index, &textures, &mut rng);
     }
 }
 
 fn spawn_collectable(
     commands: &mut Commands,
     position: Vec2,
-    meta_index: usize,
-    asset_server: &Res<AssetServer>
+    ball: Option<Entity>,
+    collectable_set: &CollectableSet,
+    definition_index: usize,
+    textures: &Textures,
+    rng: &mut GameRng,
 )
 {
-    let (collectable_type, collectable_meta) = &COLLECTABLE_METAS[meta_index];
+    let definition = &collectable_set.collectables[definition_index];
+    let extents = Vec2::new(definition.extents.0, definition.extents.1);
 
     commands.spawn(
         (
@@ -187,22 +269,72 @@ fn spawn_collectable(
                         translation: Vec3 {
                             x: position.x,
                             y: position.y,
-                            z: collectable_meta.z_order,
+                            z: definition.z_order,
                         },
-                        scale: collectable_meta.scale * Vec3::ONE,
+                        scale: definition.scale * Vec3::ONE,
                         ..default()
                     },
-                    texture: asset_server.load(collectable_meta.texture_path),
+                    texture: textures.load(definition.texture.clone()),
                     ..default()
                 },
+                spark: Spark::random_drop(rng),
                 box_collider: BoxCollider {
-                    extends: collectable_meta.scale * collectable_meta.extends,
+                    extends: definition.scale * extents,
                 },
-                ..default()
+                interpolated_transform: InterpolatedTransform::default(),
             },
             Collectable {
-                collectable_type: collectable_type.clone(),
+                definition: definition_index,
+                ball,
             },
         )
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(coins: &str, collectables: &str) -> Result<CollectableSet, CollectableLoaderError> {
+        let text = format!("(coins: ({}), collectables: [{}])", coins, collectables);
+        CollectableSet::from_bytes(text.as_bytes())
+    }
+
+    const COIN: &str = "(name: \"coin\", texture: \"coin.png\", effects: [Score(10000)])";
+    const COINS: &str = "collectable: \"coin\", min: 1, max: 3";
+
+    #[test]
+    fn rejects_unknown_coins() {
+        let result = parse("collectable: \"gem\", min: 1, max: 3", COIN);
+        assert!(matches!(result, Err(CollectableLoaderError::UnknownCoin { .. })));
+    }
+
+    #[test]
+    fn rejects_coin_ranges_past_the_most_coins() {
+        let result = parse(&format!("collectable: \"coin\", min: 0, max: {}", usize::MAX), COIN);
+        assert!(matches!(result, Err(CollectableLoaderError::InvalidCoinRange { min: 0, max: usize::MAX })));
+    }
+
+    #[test]
+    fn checks_coin_ranges_before_the_coin() {
+        let result = parse("collectable: \"gem\", min: 4, max: 3", COIN);
+        assert!(matches!(result, Err(CollectableLoaderError::InvalidCoinRange { .. })));
+    }
+
+    #[test]
+    fn rejects_invalid_effects() {
+        let effects = [
+            "Score(0)",
+            "Lives(0)",
+            "Stat(stat: BallSize, points: 0)",
+            "Stat(stat: BallSize, points: 1, duration: 0.0)",
+            "Stat(stat: BallSize, points: 1, duration: -1.0)",
+            "Stat(stat: BallSize, points: 1, max_stacks: 0)",
+        ];
+        for effect in effects {
+            let collectables = format!("{}, (name: \"bad\", texture: \"bad.png\", effects: [{}])", COIN, effect);
+            let result = parse(COINS, &collectables);
+            assert!(matches!(result, Err(CollectableLoaderError::InvalidEffect { .. })), "{}", effect);
+        }
+    }
+}
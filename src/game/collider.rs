@@ -5,6 +5,15 @@ pub struct BoxCollider {
     pub extends: Vec2,
 }
 
+/// The first contact of a moving circle.
+#[derive(Clone, Copy)]
+pub struct SweepHit {
+    /// How much of the motion happens before the contact, from 0 to 1.
+    pub time: f32,
+    /// Points from the obstacle towards the circle at the contact.
+    pub normal: Vec2,
+}
+
 impl Default for BoxCollider {
     fn default() -> Self {
         Self {
@@ -14,6 +23,11 @@ impl Default for BoxCollider {
 }
 
 impl BoxCollider {
+    /// Moves the center along x just enough for the whole box to stay between `min_x` and `max_x`.
+    pub fn clamp_center_x(&self, center_x: f32, min_x: f32, max_x: f32) -> f32 {
+        center_x.clamp(min_x + self.extends.x, (max_x - self.extends.x).max(min_x + self.extends.x))
+    }
+
     pub fn overlap(box1_center: Vec2, box1_extents: Vec2, box2_center: Vec2, box2_extents: Vec2) -> bool {
         let box1_min = box1_center - box1_extents;
         let box1_max = box1_center + box1_extents;
@@ -26,4 +40,176 @@ impl BoxCollider {
 
         x_overlap && y_overlap
     }
-}
\ No newline at end of file
+
+    pub fn overlap_circle(box_center: Vec2, box_extents: Vec2, circle_center: Vec2, radius: f32) -> bool {
+        let box_to_circle = circle_center - box_center;
+        let box_to_closest = box_to_circle.clamp(-box_extents, box_extents);
+        (box_to_circle - box_to_closest).length_squared() <= radius * radius
+    }
+
+    /// Finds where a circle moving from `start` by `motion` first touches the box.
+    ///
+    /// This is a ray cast against the box grown by the radius, with rounded corners.
+    /// A circle that already overlaps the box only hits it when it moves further in.
+    pub fn sweep_circle(box_center: Vec2, box_extents: Vec2, start: Vec2, motion: Vec2, radius: f32) -> Option<SweepHit> {
+        let start = start - box_center;
+
+        let closest = start.clamp(-box_extents, box_extents);
+        if (start - closest).length_squared() <= radius * radius {
+            let normal = get_push_out_normal(start, closest, box_extents);
+            return (motion.dot(normal) < 0.).then_some(SweepHit { time: 0., normal });
+        }
+
+        let (time, normal) = ray_cast_box(start, motion, box_extents + Vec2::splat(radius))?;
+        if time > 1. {
+            return None;
+        }
+
+        let position = start + motion * time;
+        if position.x.abs() <= box_extents.x || position.y.abs() <= box_extents.y {
+            return Some(SweepHit { time, normal });
+        }
+
+        // The grown box is entered next to a corner, where it is really a quarter circle around the corner.
+        let corner = Vec2::new(box_extents.x.copysign(position.x), box_extents.y.copysign(position.y));
+        let time = ray_cast_circle(start, motion, corner, radius)?;
+        if !(0. ..=1.).contains(&time) {
+            return None;
+        }
+        let normal = (start + motion * time - corner).normalize_or_zero();
+        Some(SweepHit { time, normal })
+    }
+}
+
+/// The way out of the box for a circle whose center is at `position` and closest to the box at `closest`.
+fn get_push_out_normal(position: Vec2, closest: Vec2, box_extents: Vec2) -> Vec2 {
+    let offset = position - closest;
+    if offset != Vec2::ZERO {
+        return offset.normalize();
+    }
+
+    // The center is inside the box, leave through the nearest side.
+    let penetration = box_extents - position.abs();
+    if penetration.x < penetration.y {
+        Vec2::new(1f32.copysign(position.x), 0.)
+    }
+    else {
+        Vec2::new(0., 1f32.copysign(position.y))
+    }
+}
+
+/// Slab test of a ray against a box centered at the origin. Gives the entry time and the normal of the entered side.
+fn ray_cast_box(start: Vec2, motion: Vec2, extents: Vec2) -> Option<(f32, Vec2)> {
+    let mut entry_time = f32::NEG_INFINITY;
+    let mut exit_time = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for axis in 0..2 {
+        if motion[axis] == 0. {
+            if start[axis].abs() > extents[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let near = (-extents[axis].copysign(motion[axis]) - start[axis]) / motion[axis];
+        let far = (extents[axis].copysign(motion[axis]) - start[axis]) / motion[axis];
+        if near > entry_time {
+            entry_time = near;
+            normal = Vec2::ZERO;
+            normal[axis] = -motion[axis].signum();
+        }
+        exit_time = exit_time.min(far);
+    }
+
+    (entry_time <= exit_time && entry_time >= 0.).then_some((entry_time, normal))
+}
+
+fn ray_cast_circle(start: Vec2, motion: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = start - center;
+    let a = motion.length_squared();
+    let b = 2. * motion.dot(offset);
+    let c = offset.length_squared() - radius * radius;
+    let discriminant = b * b - 4. * a * c;
+    if a == 0. || discriminant < 0. {
+        return None;
+    }
+    Some((-b - discriminant.sqrt()) / (2. * a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTENTS: Vec2 = Vec2::new(32., 16.);
+    const RADIUS: f32 = 10.;
+
+    #[test]
+    fn hits_a_side_head_on() {
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, Vec2::new(0., -100.), Vec2::new(0., 100.), RADIUS).unwrap();
+        assert!((hit.time - (100. - EXTENTS.y - RADIUS) / 100.).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec2::NEG_Y);
+    }
+
+    #[test]
+    fn misses_when_stopping_short() {
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, Vec2::new(0., -100.), Vec2::new(0., 50.), RADIUS);
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn misses_when_moving_away() {
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, Vec2::new(0., -100.), Vec2::new(0., -100.), RADIUS);
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn misses_without_motion() {
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, Vec2::new(0., -100.), Vec2::ZERO, RADIUS);
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn grazes_past_a_corner() {
+        // Cuts through the corner of the grown box, but passes the box corner farther than the radius.
+        let offset = 1.3 * RADIUS * std::f32::consts::FRAC_1_SQRT_2;
+        let start = EXTENTS + Vec2::new(offset - 100., offset + 100.);
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, start, Vec2::new(200., -200.), RADIUS);
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn hits_a_corner_diagonally() {
+        let start = EXTENTS + Vec2::splat(50.);
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, start, Vec2::splat(-100.), RADIUS).unwrap();
+        let contact = start + Vec2::splat(-100.) * hit.time;
+        assert!((contact.distance(EXTENTS) - RADIUS).abs() < 1e-3);
+        assert!(hit.normal.x > 0. && hit.normal.y > 0.);
+    }
+
+    #[test]
+    fn overlapping_circle_only_hits_when_moving_in() {
+        let start = Vec2::new(0., -EXTENTS.y - RADIUS / 2.);
+        let moving_in = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, start, Vec2::new(0., 10.), RADIUS).unwrap();
+        assert_eq!(moving_in.time, 0.);
+        assert_eq!(moving_in.normal, Vec2::NEG_Y);
+
+        let moving_out = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, start, Vec2::new(0., -10.), RADIUS);
+        assert!(moving_out.is_none());
+    }
+
+    #[test]
+    fn center_inside_leaves_through_the_nearest_side() {
+        let start = Vec2::new(EXTENTS.x - 2., 0.);
+        let hit = BoxCollider::sweep_circle(Vec2::ZERO, EXTENTS, start, Vec2::new(-10., 0.), RADIUS).unwrap();
+        assert_eq!(hit.normal, Vec2::X);
+    }
+
+    #[test]
+    fn works_away_from_the_origin() {
+        let center = Vec2::new(500., 300.);
+        let hit = BoxCollider::sweep_circle(center, EXTENTS, center + Vec2::new(-100., 0.), Vec2::new(100., 0.), RADIUS).unwrap();
+        assert!((hit.time - (100. - EXTENTS.x - RADIUS) / 100.).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+    }
+}
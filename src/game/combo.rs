@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use crate::game::events::{BrickDestroyed, PointsScored};
+use crate::game::paddle::PaddleContact;
+use crate::game::resources::Score;
+
+/// Points for a destroyed brick, before the combo multiplier.
+pub const BRICK_POINTS: usize = 1000;
+/// Every this many bricks destroyed in a row add one to the multiplier.
+const BRICKS_PER_MULTIPLIER_STEP: usize = 5;
+const MAX_MULTIPLIER: usize = 8;
+
+/// The bricks destroyed since a ball last touched the paddle.
+#[derive(Resource, Default)]
+pub struct Combo {
+    bricks: usize,
+}
+
+impl Combo {
+    pub fn get_multiplier(&self) -> usize {
+        (1 + self.bricks / BRICKS_PER_MULTIPLIER_STEP).min(MAX_MULTIPLIER)
+    }
+}
+
+/// Scores every destroyed brick at the multiplier of the combo it extends, a paddle contact starts a new combo.
+pub fn update_combo(
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut points_scored_events: EventWriter<PointsScored>,
+    paddle_contact: PaddleContact,
+    mut combo: ResMut<Combo>,
+    mut score: ResMut<Score>,
+)
+{
+    if paddle_contact.is_touching() && combo.bricks > 0 {
+        combo.bricks = 0;
+    }
+
+    for event in brick_destroyed_events.read() {
+        combo.bricks += 1;
+        let points = BRICK_POINTS * combo.get_multiplier();
+        score.0 += points;
+        points_scored_events.send(PointsScored {
+            position: event.brick_position,
+            points,
+        });
+    }
+}
+
+/// A fresh ball waits on the paddle, so a lost ball ends the combo.
+pub fn end_combo(
+    mut combo: ResMut<Combo>,
+)
+{
+    combo.bricks = 0;
+}
@@ -0,0 +1,271 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::game::ball::Ball;
+use crate::game::paddle::Paddle;
+use crate::game::resources::{PaddleSize, PaddleSpeed};
+
+/// One operation a collectable does when the paddle collects it, a collectable can do any number of them.
+#[derive(Clone, Copy, Deserialize)]
+pub enum CollectableEffect {
+    /// Adds to the score.
+    Score(usize),
+    /// Adds to the lives left.
+    Lives(usize),
+    /// Clones every ball in play, up to the ball limit.
+    CloneBalls,
+    /// Removes every other ball in play.
+    DecloneBalls,
+    /// Spans a shield over the bottom of the playfield, unless there already is one.
+    Shield,
+    /// Changes a stat of the balls or the paddle, for good or only for `duration` seconds of game time.
+    ///
+    /// Ball stats only change the ball that broke the brick, or every ball once that one is gone.
+    Stat {
+        stat: EffectStat,
+        /// Points each stack changes the stat by, the sign tells opposite effects apart.
+        points: i32,
+        #[serde(default)]
+        duration: Option<f32>,
+        /// Further pickups past this only restart the countdown.
+        #[serde(default = "CollectableEffect::default_max_stacks")]
+        max_stacks: usize,
+    },
+}
+
+impl CollectableEffect {
+    fn default_max_stacks() -> usize {
+        1
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match *self {
+            CollectableEffect::Score(0) => return Err("a score change needs points"),
+            CollectableEffect::Lives(0) => return Err("a lives change needs at least one life"),
+            _ => {}
+        }
+
+        if let CollectableEffect::Stat { points, duration, max_stacks, .. } = *self {
+            if points == 0 {
+                return Err("a stat change needs points");
+            }
+            if duration.is_some_and(|duration| duration <= 0.) {
+                return Err("a timed stat change needs a positive duration");
+            }
+            if max_stacks == 0 {
+                return Err("a stat change needs at least one stack");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What a stat change changes. Most stats have a pair of power-ups pulling them in opposite directions.
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum EffectStat {
+    BallSize,
+    BallSpeed,
+    Ghost,
+    PaddleSize,
+    PaddleSpeed,
+    Laser,
+    Sticky,
+}
+
+impl EffectStat {
+    /// Whether the stat belongs to each ball, the others belong to the paddle.
+    pub fn is_ball_stat(self) -> bool {
+        matches!(self, EffectStat::BallSize | EffectStat::BallSpeed | EffectStat::Ghost)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TimedEffect {
+    pub stat: EffectStat,
+    pub points: i32,
+    /// Seconds of game time until the effect wears off.
+    pub duration: f32,
+    pub max_stacks: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EffectTarget {
+    Ball(Entity),
+    Paddle,
+}
+
+#[derive(Clone)]
+struct ActiveEffect {
+    /// The index of the collectable that started the effect.
+    collectable: usize,
+    timed_effect: TimedEffect,
+    target: EffectTarget,
+    /// Seconds of game time left.
+    remaining: f32,
+    /// The points each stack actually changed, the limits of a stat can swallow some or all of them.
+    stacks: Vec<i32>,
+}
+
+/// The timed power-ups currently running, oldest first.
+#[derive(Resource, Default)]
+pub struct ActiveEffects {
+    effects: Vec<ActiveEffect>,
+}
+
+impl ActiveEffects {
+    /// Starts the timed effect of a collectable on a target, or stacks and refreshes it when it is already running.
+    ///
+    /// Picking up the opposite of a running effect takes one stack off that effect instead.
+    pub fn add(&mut self, collectable: usize, timed_effect: TimedEffect, target: EffectTarget, subjects: &mut EffectSubjects) {
+        let opposite = self.effects.iter().position(|effect| {
+            effect.target == target
+                && effect.timed_effect.stat == timed_effect.stat
+                && effect.timed_effect.points.signum() != timed_effect.points.signum()
+        });
+        if let Some(index) = opposite {
+            let effect = &mut self.effects[index];
+            let points = effect.stacks.pop().unwrap_or(0);
+            subjects.change(timed_effect.stat, target, -points);
+            if effect.stacks.is_empty() {
+                self.effects.remove(index);
+            }
+            return;
+        }
+
+        if let Some(effect) = self.effects.iter_mut().find(|effect| {
+            effect.collectable == collectable && effect.timed_effect.stat == timed_effect.stat && effect.target == target
+        }) {
+            effect.remaining = timed_effect.duration;
+            if effect.stacks.len() < timed_effect.max_stacks {
+                effect.stacks.push(subjects.change(timed_effect.stat, target, timed_effect.points));
+            }
+            return;
+        }
+
+        self.effects.push(ActiveEffect {
+            collectable,
+            timed_effect,
+            target,
+            remaining: timed_effect.duration,
+            stacks: vec![subjects.change(timed_effect.stat, target, timed_effect.points)],
+        });
+    }
+
+    /// Gives a cloned ball the running effects of its original, it already carries their changes.
+    pub fn copy_ball_effects(&mut self, original: Entity, clone: Entity) {
+        let copies: Vec<ActiveEffect> = self.effects
+            .iter()
+            .filter(|effect| effect.target == EffectTarget::Ball(original))
+            .map(|effect| ActiveEffect {
+                target: EffectTarget::Ball(clone),
+                ..effect.clone()
+            })
+            .collect();
+        self.effects.extend(copies);
+    }
+
+    /// The collectables with running effects, in the order they were first picked up.
+    pub fn get_collectables(&self) -> Vec<usize> {
+        let mut collectables: Vec<usize> = Vec::new();
+        for effect in self.effects.iter() {
+            if !collectables.contains(&effect.collectable) {
+                collectables.push(effect.collectable);
+            }
+        }
+        collectables
+    }
+
+    /// How much of the longest running effect of a collectable is left, from 1 down to 0, and its number of stacks.
+    pub fn get_progress(&self, collectable: usize) -> Option<(f32, usize)> {
+        self.effects
+            .iter()
+            .filter(|effect| effect.collectable == collectable)
+            .map(|effect| (effect.remaining / effect.timed_effect.duration, effect.stacks.len()))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    /// Counts down every effect, undoing the ones that ran out and dropping the ones of lost balls.
+    fn tick(&mut self, delta_seconds: f32, subjects: &mut EffectSubjects) {
+        self.effects.retain_mut(|effect| {
+            if let EffectTarget::Ball(ball) = effect.target {
+                if !subjects.ball_query.contains(ball) {
+                    return false;
+                }
+            }
+
+            effect.remaining -= delta_seconds;
+            if effect.remaining > 0. {
+                return true;
+            }
+
+            let points: i32 = effect.stacks.iter().sum();
+            subjects.change(effect.timed_effect.stat, effect.target, -points);
+            false
+        });
+    }
+}
+
+/// Everything stat changes can change.
+#[derive(SystemParam)]
+pub struct EffectSubjects<'w, 's> {
+    ball_query: Query<'w, 's, &'static mut Ball>,
+    paddle_query: Query<'w, 's, &'static mut Paddle>,
+    paddle_size: ResMut<'w, PaddleSize>,
+    paddle_speed: ResMut<'w, PaddleSpeed>,
+}
+
+impl EffectSubjects<'_, '_> {
+    pub fn get_ball(&self, entity: Entity) -> Option<Ball> {
+        self.ball_query.get(entity).ok().copied()
+    }
+
+    /// Changes a stat of the target, returns how many points it actually changed.
+    pub fn change(&mut self, stat: EffectStat, target: EffectTarget, points: i32) -> i32 {
+        match (stat, target) {
+            (EffectStat::PaddleSize, _) => self.paddle_size.change_points(points),
+            (EffectStat::PaddleSpeed, _) => self.paddle_speed.change_points(points),
+            (EffectStat::Laser, _) => match self.paddle_query.get_single_mut() {
+                Ok(mut paddle) => change_flag(&mut paddle.laser, points),
+                Err(_) => 0,
+            },
+            (EffectStat::Sticky, _) => match self.paddle_query.get_single_mut() {
+                Ok(mut paddle) => change_flag(&mut paddle.sticky, points),
+                Err(_) => 0,
+            },
+            (_, EffectTarget::Paddle) => 0,
+            (_, EffectTarget::Ball(entity)) => {
+                let Ok(mut ball) = self.ball_query.get_mut(entity) else {
+                    return 0;
+                };
+                match stat {
+                    EffectStat::BallSize => ball.size.change_points(points),
+                    EffectStat::BallSpeed => ball.speed.change_points(points),
+                    EffectStat::Ghost => change_flag(&mut ball.ghost, points),
+                    _ => 0,
+                }
+            }
+        }
+    }
+}
+
+/// Turns a flag on for positive points and off for negative ones, a flag already set that way does not count as a change.
+fn change_flag(flag: &mut bool, points: i32) -> i32 {
+    let value = points > 0;
+    if *flag == value {
+        0
+    }
+    else {
+        *flag = value;
+        points.signum()
+    }
+}
+
+pub fn update_active_effects(
+    mut active_effects: ResMut<ActiveEffects>,
+    mut effect_subjects: EffectSubjects,
+    time: Res<Time>,
+)
+{
+    active_effects.tick(time.delta_seconds(), &mut effect_subjects);
+}
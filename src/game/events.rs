@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use crate::game::brick::BrickKind;
 
 #[derive(Event, Default)]
 pub struct LastBallDestroyed;
@@ -9,8 +10,32 @@ pub struct RestartRequested;
 pub struct MenuRequested;
 #[derive(Event, Default)]
 pub struct TogglePauseRequested;
+#[derive(Event, Default)]
+pub struct ReplayRequested;
+#[derive(Event, Default)]
+pub struct NextStageRequested;
 
 #[derive(Event, Default)]
 pub struct BrickDestroyed {
     pub brick_position: Vec2,
-}
\ No newline at end of file
+    pub brick_kind: BrickKind,
+    /// The ball that took the brick down, directly or through an explosion. None when lasers shot it down.
+    pub ball: Option<Entity>,
+}
+
+/// Sent for every collectable the paddle picks up.
+#[derive(Event)]
+pub struct CollectableCollected {
+    /// Index into the collectables of the current set.
+    pub definition: usize,
+}
+
+/// Sent for the points a destroyed brick scored, combo multiplier included.
+#[derive(Event)]
+pub struct PointsScored {
+    pub position: Vec2,
+    pub points: usize,
+}
+
+#[derive(Event, Default)]
+pub struct LevelCompleted;
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::game::campaign::CarryOver;
+use crate::game::InGameState;
+use crate::game::replay::ReplayPlayback;
+
+/// How long a time attack lasts, in seconds of game time.
+pub const TIME_ATTACK_SECONDS: f32 = 180.;
+
+/// The rules the game is played by, picked in the mode select and kept when restarting.
+#[derive(Resource, Clone, Copy, Default, PartialEq)]
+pub enum GameMode {
+    /// New rows of bricks keep coming, the game only ends once every life is lost.
+    #[default]
+    Endless,
+    /// Score as much as possible before [`TIME_ATTACK_SECONDS`] run out.
+    TimeAttack,
+    /// New rows of bricks are pushed in faster and faster, the game ends once the bricks get down to the paddle.
+    AdvancingWall,
+    /// The campaign stages in order, starting with `stage`.
+    Campaign {
+        /// Index into [`CAMPAIGN_STAGE_PATHS`](crate::game::campaign::CAMPAIGN_STAGE_PATHS).
+        stage: usize,
+        /// None for a stage started from the stage select, it starts fresh.
+        carry_over: Option<CarryOver>,
+    },
+}
+
+impl GameMode {
+    pub fn get_kind(&self) -> GameModeKind {
+        match self {
+            GameMode::Endless => GameModeKind::Endless,
+            GameMode::TimeAttack => GameModeKind::TimeAttack,
+            GameMode::AdvancingWall => GameModeKind::AdvancingWall,
+            GameMode::Campaign { .. } => GameModeKind::Campaign,
+        }
+    }
+}
+
+/// A game mode without its settings, every campaign stage counts as the campaign. Scores are only compared within one kind.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum GameModeKind {
+    Endless,
+    TimeAttack,
+    AdvancingWall,
+    Campaign,
+}
+
+impl GameModeKind {
+    pub const ALL: [GameModeKind; 4] = [
+        GameModeKind::Endless,
+        GameModeKind::TimeAttack,
+        GameModeKind::AdvancingWall,
+        GameModeKind::Campaign,
+    ];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            GameModeKind::Endless => "Endless",
+            GameModeKind::TimeAttack => "Time Attack",
+            GameModeKind::AdvancingWall => "Advancing Wall",
+            GameModeKind::Campaign => "Campaign",
+        }
+    }
+
+    /// The kind after this one in [`GameModeKind::ALL`], wrapping around.
+    pub fn get_next(self) -> GameModeKind {
+        let index = Self::ALL.iter().position(|kind| *kind == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Why the latest game ended, shown on the summary screen.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+pub enum GameOverReason {
+    OutOfLives,
+    LevelCompleted,
+    CampaignCompleted,
+    TimeUp,
+    WallReachedPaddle,
+}
+
+impl GameOverReason {
+    pub fn get_description(self) -> &'static str {
+        match self {
+            GameOverReason::OutOfLives => "No lives left",
+            GameOverReason::LevelCompleted => "Level completed",
+            GameOverReason::CampaignCompleted => "Campaign completed",
+            GameOverReason::TimeUp => "Time is up",
+            GameOverReason::WallReachedPaddle => "The wall reached the paddle",
+        }
+    }
+}
+
+/// The game time left in a time attack.
+#[derive(Resource)]
+pub struct RemainingTime(pub f32);
+
+impl Default for RemainingTime {
+    fn default() -> Self {
+        Self(TIME_ATTACK_SECONDS)
+    }
+}
+
+/// A replay is watched by the rules it was recorded with.
+pub fn apply_replay_game_mode(
+    replay_playback: Option<Res<ReplayPlayback>>,
+    mut game_mode: ResMut<GameMode>,
+)
+{
+    if let Some(replay_playback) = replay_playback {
+        *game_mode = replay_playback.get_replay().mode;
+    }
+}
+
+/// Ends a time attack once its time is up.
+pub fn count_down_remaining_time(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    mut remaining_time: ResMut<RemainingTime>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<InGameState>>,
+)
+{
+    if *game_mode != GameMode::TimeAttack {
+        return;
+    }
+
+    remaining_time.0 = (remaining_time.0 - time.delta_seconds()).max(0.);
+    if remaining_time.0 <= 0. {
+        commands.insert_resource(GameOverReason::TimeUp);
+        next_state.set(InGameState::Summary);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::common::storage::{load_data_file, save_data_file};
+use crate::game::game_mode::GameModeKind;
+
+const HIGH_SCORES_FILE_NAME: &str = "high_scores.ron";
+const HIGH_SCORES_VERSION: u32 = 1;
+pub const MAX_NUMBER_OF_HIGH_SCORES: usize = 10;
+pub const MAX_PLAYER_NAME_LENGTH: usize = 12;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: usize,
+    pub seed: u64,
+}
+
+/// The best scores ever reached on this machine, a table for each kind of game mode.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    /// Highest first, kinds without any score yet are left out.
+    tables: BTreeMap<GameModeKind, Vec<HighScore>>,
+    /// Offered again the next time a name has to be entered.
+    last_name: String,
+}
+
+/// The file keeps its own version, so older tables can still be recognized after the format changes.
+#[derive(Serialize, Deserialize)]
+struct HighScoresFile {
+    version: u32,
+    table: HighScores,
+}
+
+impl HighScores {
+    pub fn get_entries(&self, mode: GameModeKind) -> &[HighScore] {
+        self.tables.get(&mode).map_or(&[], |entries| entries.as_slice())
+    }
+
+    pub fn get_last_name(&self) -> &str {
+        &self.last_name
+    }
+
+    pub fn qualifies(&self, mode: GameModeKind, score: usize) -> bool {
+        let entries = self.get_entries(mode);
+        score > 0 && (entries.len() < MAX_NUMBER_OF_HIGH_SCORES
+            || entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Adds the score to the table of the mode and returns its rank, starting at 0.
+    pub fn insert(&mut self, mode: GameModeKind, high_score: HighScore) -> Option<usize> {
+        if !self.qualifies(mode, high_score.score) {
+            return None;
+        }
+
+        self.last_name = high_score.name.clone();
+        let entries = self.tables.entry(mode).or_default();
+        let rank = entries.iter().position(|entry| entry.score < high_score.score).unwrap_or(entries.len());
+        entries.insert(rank, high_score);
+        entries.truncate(MAX_NUMBER_OF_HIGH_SCORES);
+        Some(rank)
+    }
+
+    /// Reads the table from the data directory, an empty table is used when there is none or it cannot be read.
+    pub fn load() -> Self {
+        let Some(file) = load_data_file::<HighScoresFile>(HIGH_SCORES_FILE_NAME, HIGH_SCORES_VERSION, "high scores") else {
+            return Self::default();
+        };
+
+        let mut table = file.table;
+        for entries in table.tables.values_mut() {
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+            entries.truncate(MAX_NUMBER_OF_HIGH_SCORES);
+        }
+        table
+    }
+
+    pub fn save(&self) {
+        let file = HighScoresFile {
+            version: HIGH_SCORES_VERSION,
+            table: self.clone(),
+        };
+        save_data_file(HIGH_SCORES_FILE_NAME, &file, "high scores");
+    }
+}
+
+pub fn load_high_scores(
+    mut commands: Commands,
+)
+{
+    commands.insert_resource(HighScores::load());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_high_score(score: usize) -> HighScore {
+        HighScore {
+            name: "Player".to_string(),
+            score,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_a_table_for_each_mode() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(GameModeKind::Endless, get_high_score(2000)), Some(0));
+        assert_eq!(high_scores.insert(GameModeKind::TimeAttack, get_high_score(1000)), Some(0));
+        assert_eq!(high_scores.insert(GameModeKind::Endless, get_high_score(3000)), Some(0));
+
+        let scores = |mode| high_scores.get_entries(mode).iter().map(|entry| entry.score).collect::<Vec<_>>();
+        assert_eq!(scores(GameModeKind::Endless), vec![3000, 2000]);
+        assert_eq!(scores(GameModeKind::TimeAttack), vec![1000]);
+        assert!(scores(GameModeKind::Campaign).is_empty());
+    }
+
+    #[test]
+    fn only_takes_scores_beating_a_full_table() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_NUMBER_OF_HIGH_SCORES {
+            high_scores.insert(GameModeKind::Endless, get_high_score(score * 100));
+        }
+
+        assert!(!high_scores.qualifies(GameModeKind::Endless, 100));
+        assert!(high_scores.qualifies(GameModeKind::Endless, 101));
+        assert!(high_scores.qualifies(GameModeKind::AdvancingWall, 100));
+        assert!(!high_scores.qualifies(GameModeKind::AdvancingWall, 0));
+    }
+}
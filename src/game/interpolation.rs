@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+/// Shows a simulated entity between its last two simulation steps, so it moves smoothly at any frame rate.
+///
+/// The simulation only ever sees the transform of the latest step, the blended one is put in place for rendering.
+#[derive(Component, Default)]
+pub struct InterpolatedTransform {
+    /// The transform before the latest step, none until the entity went through a step.
+    previous: Option<Transform>,
+    /// The transform after the latest step.
+    current: Option<Transform>,
+}
+
+/// Puts back the transforms of the latest step, replacing the blended ones shown in the previous frame.
+pub fn restore_simulated_transforms(world: &mut World) {
+    let mut query = world.query::<(&mut Transform, &InterpolatedTransform)>();
+    for (mut transform, interpolated_transform) in query.iter_mut(world) {
+        if let Some(current) = interpolated_transform.current {
+            *transform = current;
+        }
+    }
+}
+
+/// Remembers where every entity was before the step that is about to run.
+pub fn store_previous_transforms(world: &mut World) {
+    let mut query = world.query::<(&Transform, &mut InterpolatedTransform)>();
+    for (transform, mut interpolated_transform) in query.iter_mut(world) {
+        interpolated_transform.previous = Some(*transform);
+    }
+}
+
+/// Blends the last two steps, `alpha` is how far the frame time got towards the next step.
+pub fn interpolate_transforms(world: &mut World, alpha: f32) {
+    let mut query = world.query::<(&mut Transform, &mut InterpolatedTransform)>();
+    for (mut transform, mut interpolated_transform) in query.iter_mut(world) {
+        let current = *transform;
+        interpolated_transform.current = Some(current);
+
+        if let Some(previous) = interpolated_transform.previous {
+            *transform = Transform {
+                translation: previous.translation.lerp(current.translation, alpha),
+                rotation: previous.rotation.slerp(current.rotation, alpha),
+                scale: previous.scale.lerp(current.scale, alpha),
+            };
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadState};
+use bevy::asset::io::Reader;
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::Deserialize;
+use thiserror::Error;
+use crate::AppState;
+use crate::game::campaign::CAMPAIGN_STAGE_PATHS;
+use crate::game::game_mode::GameMode;
+use crate::game::collectable::CurrentCollectableSet;
+use crate::launch_options::LaunchOptions;
+use crate::game::brick::{BRICK_WIDTH, BrickKind, MAX_NUMBER_OF_ROWS, NUMBER_OF_BRICKS_IN_ROW};
+use crate::game::InGameState;
+use crate::game::replay::ReplayPlayback;
+use crate::game::resources::{BallSize, BallSpeed, GameRng, Lives, PaddleDeflection, PaddleSize, PaddleSpeed};
+
+pub const DEFAULT_LEVEL_PATH: &str = "levels/endless.level.ron";
+pub const EMPTY_BRICK_SYMBOL: char = '.';
+
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Level {
+    pub bricks: HashMap<char, LevelBrick>,
+    pub layout: Vec<String>,
+    #[serde(default)]
+    pub ball: LevelBall,
+    #[serde(default)]
+    pub paddle: LevelPaddle,
+    pub win_condition: WinCondition,
+    /// How many balls the player may lose before the game is over.
+    #[serde(default = "Level::default_lives")]
+    pub lives: usize,
+    /// Makes every run of the level play out the same, a random seed is used for each run otherwise.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct LevelBrick {
+    #[serde(default)]
+    pub kind: BrickKind,
+    #[serde(default = "LevelBrick::default_hit_points")]
+    pub hit_points: u32,
+    pub texture: String,
+    #[serde(default = "LevelBrick::default_color")]
+    pub color: (f32, f32, f32),
+    /// Appearance changes applied after the first, second, ... hit. The last one is kept for further hits.
+    #[serde(default)]
+    pub damage_stages: Vec<BrickDamageStage>,
+    #[serde(default = "LevelBrick::default_explosion_radius")]
+    pub explosion_radius: f32,
+}
+
+impl LevelBrick {
+    fn default_hit_points() -> u32 {
+        1
+    }
+
+    fn default_color() -> (f32, f32, f32) {
+        (1.0, 1.0, 1.0)
+    }
+
+    fn default_explosion_radius() -> f32 {
+        1.5 * BRICK_WIDTH
+    }
+
+    pub fn get_damage_stage(&self, hits_taken: u32) -> Option<&BrickDamageStage> {
+        if hits_taken == 0 {
+            return None;
+        }
+
+        let stage_index = (hits_taken as usize - 1).min(self.damage_stages.len().checked_sub(1)?);
+        self.damage_stages.get(stage_index)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BrickDamageStage {
+    #[serde(default)]
+    pub texture: Option<String>,
+    #[serde(default)]
+    pub color: Option<(f32, f32, f32)>,
+}
+
+#[derive(Deserialize)]
+pub struct LevelBall {
+    pub speed: usize,
+    pub size: usize,
+}
+
+impl Default for LevelBall {
+    fn default() -> Self {
+        Self {
+            speed: BallSpeed::DEFAULT_POINTS,
+            size: BallSize::DEFAULT_POINTS,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LevelPaddle {
+    pub speed: usize,
+    pub size: usize,
+    #[serde(default)]
+    pub deflection: PaddleDeflection,
+}
+
+impl Default for LevelPaddle {
+    fn default() -> Self {
+        Self {
+            speed: PaddleSpeed::DEFAULT_POINTS,
+            size: PaddleSize::DEFAULT_POINTS,
+            deflection: PaddleDeflection::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub enum WinCondition {
+    /// The level never ends, new rows keep coming while there are less than `min_bricks` on the board.
+    Endless {
+        refill_row: String,
+        min_bricks: usize,
+        max_rows: usize,
+    },
+    /// The level is won once there are no bricks left.
+    ClearAllBricks,
+    /// The level is won once the score reaches the given value.
+    ReachScore(usize),
+}
+
+impl Level {
+    fn default_lives() -> usize {
+        Lives::DEFAULT
+    }
+
+    /// Parses a level file and checks that it can be played.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, LevelLoaderError> {
+        let level = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_bytes::<Level>(bytes)?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    pub fn brick_rows(&self) -> impl Iterator<Item = &str> {
+        self.layout.iter().map(String::as_str)
+    }
+
+    fn validate(&self) -> Result<(), LevelLoaderError> {
+        if self.lives == 0 {
+            return Err(LevelLoaderError::NoLives);
+        }
+
+        if self.layout.len() > MAX_NUMBER_OF_ROWS {
+            return Err(LevelLoaderError::TooManyRows {
+                rows: self.layout.len(),
+                max: MAX_NUMBER_OF_ROWS,
+            });
+        }
+
+        for (symbol, brick) in self.bricks.iter() {
+            if brick.hit_points == 0 {
+                return Err(LevelLoaderError::NoHitPoints { symbol: *symbol });
+            }
+        }
+
+        for (row_index, row) in self.layout.iter().enumerate() {
+            self.validate_row(row_index, row)?;
+        }
+
+        if let WinCondition::Endless { refill_row, max_rows, .. } = &self.win_condition {
+            if *max_rows > MAX_NUMBER_OF_ROWS {
+                return Err(LevelLoaderError::TooManyRows {
+                    rows: *max_rows,
+                    max: MAX_NUMBER_OF_ROWS,
+                });
+            }
+            self.validate_row(self.layout.len(), refill_row)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_row(&self, row_index: usize, row: &str) -> Result<(), LevelLoaderError> {
+        let length = row.chars().count();
+        if length > NUMBER_OF_BRICKS_IN_ROW {
+            return Err(LevelLoaderError::RowTooLong {
+                row: row_index,
+                length,
+                max: NUMBER_OF_BRICKS_IN_ROW,
+            });
+        }
+
+        for symbol in row.chars() {
+            if symbol != EMPTY_BRICK_SYMBOL && !self.bricks.contains_key(&symbol) {
+                return Err(LevelLoaderError::UnknownBrick {
+                    row: row_index,
+                    symbol,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("could not read the level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the level file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("the level has {rows} rows, but at most {max} fit on the screen")]
+    TooManyRows { rows: usize, max: usize },
+    #[error("row {row} has {length} bricks, but at most {max} fit in a row")]
+    RowTooLong { row: usize, length: usize, max: usize },
+    #[error("the level needs at least one life")]
+    NoLives,
+    #[error("brick '{symbol}' needs at least one hit point")]
+    NoHitPoints { symbol: char },
+    #[error("row {row} uses '{symbol}', which is not defined in `bricks`")]
+    UnknownBrick { row: usize, symbol: char },
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>>
+    {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Level::from_bytes(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct CurrentLevel {
+    pub path: String,
+    pub handle: Handle<Level>,
+}
+
+impl CurrentLevel {
+    pub fn get<'a>(&self, levels: &'a Assets<Level>) -> Option<&'a Level> {
+        levels.get(&self.handle)
+    }
+}
+
+pub fn load_start_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+)
+{
+    let path = match replay_playback {
+        Some(replay_playback) => replay_playback.get_replay().level_path.clone(),
+        None => DEFAULT_LEVEL_PATH.to_string(),
+    };
+
+    commands.insert_resource(CurrentLevel {
+        handle: asset_server.load(path.clone()),
+        path,
+    });
+}
+
+/// Switches to the level of the replay or the campaign stage being played, or back to the default level.
+pub fn select_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+    game_mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+)
+{
+    let path = match (&replay_playback, *game_mode) {
+        (Some(replay_playback), _) => replay_playback.get_replay().level_path.as_str(),
+        (None, GameMode::Campaign { stage, .. }) => CAMPAIGN_STAGE_PATHS[stage],
+        (None, GameMode::Endless | GameMode::TimeAttack | GameMode::AdvancingWall) => DEFAULT_LEVEL_PATH,
+    };
+
+    if path != current_level.path {
+        commands.insert_resource(CurrentLevel {
+            path: path.to_string(),
+            handle: asset_server.load(path.to_string()),
+        });
+    }
+}
+
+/// Waits for the level and the collectables it drops.
+pub fn check_level_loaded(
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    current_collectable_set: Res<CurrentCollectableSet>,
+    mut next_in_game_state: ResMut<NextState<InGameState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+)
+{
+    match asset_server.load_state(&current_collectable_set.handle) {
+        LoadState::Loaded => { /* go on with the level */ }
+        LoadState::Failed => {
+            error!("Collectables '{}' could not be loaded, returning to the menu.", current_collectable_set.path);
+            next_app_state.set(AppState::Menu);
+            return;
+        }
+        LoadState::NotLoaded | LoadState::Loading => {
+            return;
+        }
+    }
+
+    match asset_server.load_state(&current_level.handle) {
+        LoadState::Loaded => {
+            next_in_game_state.set(InGameState::Preparation);
+        }
+        LoadState::Failed => {
+            error!("Level '{}' could not be loaded, returning to the menu.", current_level.path);
+            next_app_state.set(AppState::Menu);
+        }
+        LoadState::NotLoaded | LoadState::Loading => { /* keep waiting */ }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_level_settings(
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    mut ball_size: ResMut<BallSize>,
+    mut ball_speed: ResMut<BallSpeed>,
+    mut paddle_size: ResMut<PaddleSize>,
+    mut paddle_speed: ResMut<PaddleSpeed>,
+    mut paddle_deflection: ResMut<PaddleDeflection>,
+    mut lives: ResMut<Lives>,
+)
+{
+    if let Some(level) = current_level.get(&levels) {
+        ball_size.set_points(level.ball.size);
+        ball_speed.set_points(level.ball.speed);
+        paddle_size.set_points(level.paddle.size);
+        paddle_speed.set_points(level.paddle.speed);
+        *paddle_deflection = level.paddle.deflection;
+        lives.0 = level.lives;
+    }
+}
+
+pub fn seed_game_rng(
+    replay_playback: Option<Res<ReplayPlayback>>,
+    launch_options: Option<Res<LaunchOptions>>,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    mut game_rng: ResMut<GameRng>,
+)
+{
+    let level_seed = current_level.get(&levels).and_then(|level| level.seed);
+    let replay_seed = replay_playback.map(|playback| playback.get_replay().seed);
+    let seed = replay_seed
+        .or(launch_options.and_then(|options| options.seed))
+        .or(level_seed);
+
+    *game_rng = match seed {
+        Some(seed) => GameRng::new(seed),
+        None => GameRng::default(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(layout: &str, win_condition: &str) -> Result<Level, LevelLoaderError> {
+        let text = format!("(bricks: {{ 'b': (texture: \"brick.png\"), 'é': (texture: \"brick.png\") }}, layout: [{}], win_condition: {})", layout, win_condition);
+        Level::from_bytes(text.as_bytes())
+    }
+
+    #[test]
+    fn counts_row_lengths_in_symbols() {
+        let full_row = format!("\"{}\"", "é".repeat(NUMBER_OF_BRICKS_IN_ROW));
+        assert!(parse(&full_row, "ClearAllBricks").is_ok());
+
+        let long_row = format!("\"{}\"", "é".repeat(NUMBER_OF_BRICKS_IN_ROW + 1));
+        let result = parse(&long_row, "ClearAllBricks");
+        assert!(matches!(result, Err(LevelLoaderError::RowTooLong { row: 0, .. })));
+    }
+
+    #[test]
+    fn checks_the_refill_row_as_the_row_after_the_layout() {
+        let result = parse("\"b\"", "Endless(refill_row: \"bx\", min_bricks: 10, max_rows: 8)");
+        assert!(matches!(result, Err(LevelLoaderError::UnknownBrick { row: 1, symbol: 'x' })));
+    }
+
+    #[test]
+    fn caps_the_rows_endless_levels_grow_to() {
+        let win_condition = format!("Endless(refill_row: \"b\", min_bricks: 10, max_rows: {})", MAX_NUMBER_OF_ROWS + 1);
+        let result = parse("\"b\"", &win_condition);
+        assert!(matches!(result, Err(LevelLoaderError::TooManyRows { .. })));
+    }
+}
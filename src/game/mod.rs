@@ -1,7 +1,7 @@
 pub mod ball;
 pub mod collider;
-mod brick;
-mod paddle;
+pub mod brick;
+pub mod paddle;
 pub mod resources;
 mod shared;
 pub mod events;
@@ -11,34 +11,78 @@ mod score_view;
 mod summary_view;
 mod pause_view;
 mod preparation_view;
+pub mod level;
+pub mod textures;
+pub mod player_input;
+pub mod replay;
+mod replay_view;
+pub mod high_scores;
+pub mod interpolation;
+pub mod trajectory;
+pub mod broad_phase;
+mod effects;
+pub mod power_ups;
+pub mod campaign;
+mod stage_clear_view;
+pub mod game_mode;
+mod statistics;
+mod combo;
+mod score_popup_view;
 
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
 use crate::{AppState};
+use crate::common::better_button::reset_button_focus;
+use crate::common::actions::{Action, ActionInput};
+use crate::common::settings::Settings;
 
 use paddle::{despawn_paddles, spawn_paddle, move_paddle, keep_paddle_synced_with_settings};
 use ball::{ spawn_first_ball, move_balls, despawn_balls };
 use brick::{ despawn_bricks, destroy_bricks_on_hit, spawn_bricks };
-use crate::game::ball::{keep_ball_synced_with_settings, keep_destroying_balls};
-use crate::game::brick::{keep_brick_synced_with_settings, keep_spawning_bricks};
-use crate::game::events::{BrickDestroyed, LastBallDestroyed, RestartRequested, MenuRequested, TogglePauseRequested};
-use crate::game::collectable::{despawn_collectables, keep_spawning_collectables};
+use crate::game::ball::{keep_ball_synced_with_state, keep_destroying_balls};
+use crate::game::brick::{check_level_completed, check_wall_reached_paddle, keep_advancing_wall, keep_spawning_bricks, spawn_danger_line};
+use crate::game::events::{BrickDestroyed, CollectableCollected, LastBallDestroyed, PointsScored, LevelCompleted, RestartRequested, MenuRequested, TogglePauseRequested, ReplayRequested, NextStageRequested};
+use crate::game::level::{Level, LevelLoader, apply_level_settings, check_level_loaded, load_start_level, seed_game_rng, select_level};
+use crate::game::player_input::{PlayerInput, read_player_input};
+use crate::game::replay::{GameStep, GameStepClock, ReplayPlayback, ReplayRecorder, check_replay_requested, check_replay_rewind, finish_recording, reset_game_step_clock, rewind_replay_playback, run_game_steps, start_recording, stop_replay};
+use crate::game::replay_view::{check_replay_view_interactions, despawn_replay_view, spawn_replay_view, update_replay_view};
+use crate::game::collectable::{CollectableSet, CollectableSetLoader, despawn_collectables, keep_spawning_collectables, load_collectable_set};
 use crate::game::pause_view::{spawn_pause_view, despawn_pause_view, check_pause_interactions};
 use crate::game::preparation_view::{despawn_preparation_view, spawn_preparation_view};
-use crate::game::score_view::{despawn_score_view, spawn_score_view, update_score_view};
-use crate::game::resources::{BallSize, BallSpeed, BrickGhost, PaddleSize, PaddleSpeed, Score, BrickRowSpawnCooldown};
+use crate::game::score_view::{despawn_score_view, spawn_score_view, update_active_effects_view, update_combo_view, update_lives_view, update_remaining_time_view, update_score_view};
+use crate::game::resources::{BallSize, BallSpeed, BrickGhost, Lives, PaddleDeflection, PaddleSize, PaddleSpeed, Score, BrickRowSpawnCooldown, GameRng, WallAdvanceTimer};
 use crate::game::shared::{collect_collectables, keep_ball_at_paddle_center};
 use crate::game::spark::{keep_despawning_sparks, move_sparks};
-use crate::game::summary_view::{check_summary_interactions, despawn_summary_view, spawn_summary_view};
+use crate::game::summary_view::{PendingHighScore, check_name_entry, check_summary_interactions, despawn_summary_view, spawn_summary_view};
+use crate::game::high_scores::load_high_scores;
+use crate::game::broad_phase::{CollisionGrid, update_collision_grid};
+use crate::game::trajectory::{BallLoopDetected, supervise_ball_trajectories};
+use crate::game::effects::{ActiveEffects, update_active_effects};
+use crate::game::statistics::{RunStatistics, end_rally, update_run_statistics};
+use crate::game::combo::{Combo, end_combo, update_combo};
+use crate::game::score_popup_view::{despawn_score_popups, spawn_score_popups, update_score_popups};
+use crate::game::power_ups::{break_shields_on_hit, despawn_power_ups, fire_lasers, keep_stuck_balls_on_paddle, move_lasers};
+use crate::game::campaign::{apply_carry_over, award_stage_bonus, check_next_stage_condition, load_campaign_progress, unlock_next_stage};
+use crate::game::game_mode::{GameMode, GameOverReason, RemainingTime, apply_replay_game_mode, count_down_remaining_time};
+use crate::game::stage_clear_view::{check_stage_clear_interactions, despawn_stage_clear_view, spawn_stage_clear_view};
 
+/// Game logic, runs without a window or any rendering.
 pub struct GamePlugin;
 
+/// Everything players see on top of the playfield: the score, the preparation, pause, stage clear and summary views.
+pub struct GameViewPlugin;
+
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-enum InGameState {
+pub enum InGameState {
     #[default]
     None,
+    Loading,
     Preparation,
     Play,
     Pause,
+    /// A campaign stage was cleared, the next one is about to be loaded.
+    StageClear,
     Summary,
 }
 
@@ -46,78 +90,183 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app
             .add_state::<InGameState>()
+            .init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset::<CollectableSet>()
+            .init_asset_loader::<CollectableSetLoader>()
             .init_resource::<Score>()
             .init_resource::<BrickRowSpawnCooldown>()
+            .init_resource::<WallAdvanceTimer>()
             .init_resource::<BallSize>()
             .init_resource::<BallSpeed>()
             .init_resource::<BrickGhost>()
             .init_resource::<PaddleSize>()
             .init_resource::<PaddleSpeed>()
+            .init_resource::<PaddleDeflection>()
+            .init_resource::<GameRng>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<GameStepClock>()
+            .init_resource::<CollisionGrid>()
+            .init_resource::<ActiveEffects>()
+            .init_resource::<Lives>()
+            .init_resource::<GameMode>()
+            .init_resource::<RemainingTime>()
+            .init_resource::<RunStatistics>()
+            .init_resource::<Combo>()
+            .init_resource::<Settings>()
             .add_event::<BrickDestroyed>()
             .add_event::<LastBallDestroyed>()
             .add_event::<RestartRequested>()
             .add_event::<MenuRequested>()
             .add_event::<TogglePauseRequested>()
+            .add_event::<LevelCompleted>()
+            .add_event::<ReplayRequested>()
+            .add_event::<NextStageRequested>()
+            .add_event::<BallLoopDetected>()
+            .add_event::<CollectableCollected>()
+            .add_event::<PointsScored>()
+            .add_systems(Startup, (load_start_level, load_collectable_set, start_replay_from_launch, load_campaign_progress))
+            .add_systems(OnTransition { from: AppState::InGame, to: AppState::Menu }, stop_replay)
             .add_systems(OnEnter(AppState::RestartInGame), continue_restart_game)
-            .add_systems(OnEnter(AppState::InGame),
+            .add_systems(OnEnter(AppState::InGame), ((apply_replay_game_mode, select_level).chain(), start_up, reset_game_step_clock, rewind_replay_playback))
+            .add_systems(OnTransition { from: InGameState::Loading, to: InGameState::Preparation },
                 (
-                    spawn_score_view,
-                    spawn_first_ball,
+                    apply_level_settings,
+                    apply_carry_over,
+                    seed_game_rng,
+                    start_recording,
                     spawn_paddle,
+                    spawn_first_ball,
                     spawn_bricks,
-                    start_up,
-                )
+                    spawn_danger_line,
+                ).chain()
             )
+            .add_systems(OnEnter(InGameState::Preparation), (end_rally, end_combo))
+            .add_systems(OnEnter(InGameState::Summary), finish_recording)
+            .add_systems(OnEnter(InGameState::StageClear), (finish_recording, award_stage_bonus, unlock_next_stage))
             .add_systems(OnExit(AppState::InGame),
                          (
-                             despawn_score_view,
                              despawn_balls,
                              despawn_paddles,
                              despawn_bricks,
                              despawn_collectables,
+                             despawn_power_ups,
+                             finish_recording,
                              clean_up,
                          )
             )
-            .add_systems(OnEnter(InGameState::Preparation), spawn_preparation_view)
-            .add_systems(OnExit(InGameState::Preparation), despawn_preparation_view)
-            .add_systems(OnEnter(InGameState::Pause), spawn_pause_view)
-            .add_systems(OnExit(InGameState::Pause), despawn_pause_view)
-            .add_systems(OnEnter(InGameState::Summary), spawn_summary_view)
-            .add_systems(OnExit(InGameState::Summary), despawn_summary_view)
-            .add_systems(Update,
+            .add_systems(PreUpdate, read_player_input
+                .after(InputSystem)
+                .run_if(not(resource_exists::<ReplayPlayback>()))
+            )
+            .edit_schedule(GameStep, |schedule| {
+                // Replays have to run the systems in exactly the same order as the recorded game.
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            })
+            .add_systems(GameStep,
                  (
                      (
-                         (move_paddle, keep_ball_at_paddle_center).chain(),
+                         move_paddle,
+                         keep_ball_at_paddle_center,
                          check_preparation_end_condition,
-                     ).run_if(in_state(InGameState::Preparation)),
+                     ).chain().run_if(in_state(InGameState::Preparation)),
                      (
-                         update_score_view,
                          move_paddle,
+                         keep_stuck_balls_on_paddle,
+                         fire_lasers,
+                         update_collision_grid,
                          move_balls,
+                         move_lasers,
+                         break_shields_on_hit,
+                         supervise_ball_trajectories,
                          destroy_bricks_on_hit,
-                         test_settings,
-                         keep_ball_synced_with_settings,
+                         keep_ball_synced_with_state,
                          keep_destroying_balls,
                          keep_paddle_synced_with_settings,
-                         keep_brick_synced_with_settings,
-                         keep_spawning_bricks,
+                         (
+                             keep_spawning_bricks,
+                             keep_advancing_wall,
+                         ).chain(),
                          keep_spawning_collectables,
                          move_sparks,
                          keep_despawning_sparks,
+                         update_active_effects,
+                         // Bricks got destroyed, spawned or moved by the wall and collectables dropped since the first update.
+                         update_collision_grid,
                          collect_collectables,
+                         (
+                             update_combo,
+                             update_run_statistics,
+                             check_level_completed,
+                             count_down_remaining_time,
+                             check_wall_reached_paddle,
+                         ).chain(),
+                     ).chain().run_if(in_state(InGameState::Play)),
+                 )
+            )
+            .add_systems(Update,
+                 (
+                     (
+                         check_level_loaded,
+                     ).run_if(in_state(InGameState::Loading)),
+                     (
+                         check_replay_rewind,
+                     ).run_if(resource_exists::<ReplayPlayback>()),
+                     run_game_steps,
+                     (
+                         check_menu_condition,
+                         check_restart_condition,
+                         check_replay_requested,
+                         check_toggle_pause_condition,
+                         check_summary_condition,
+                         check_next_stage_condition,
+                     ).after(run_game_steps),
+                 ).run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+impl Plugin for GameViewPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, load_high_scores)
+            .add_systems(PreUpdate, reset_button_focus.run_if(state_changed::<InGameState>()))
+            .add_systems(OnEnter(AppState::InGame), (spawn_score_view.after(apply_replay_game_mode), spawn_replay_view))
+            .add_systems(OnExit(AppState::InGame), (despawn_score_view, despawn_replay_view, despawn_score_popups))
+            .add_systems(OnEnter(InGameState::Preparation), spawn_preparation_view)
+            .add_systems(OnExit(InGameState::Preparation), despawn_preparation_view)
+            .add_systems(OnEnter(InGameState::Pause), spawn_pause_view)
+            .add_systems(OnExit(InGameState::Pause), despawn_pause_view)
+            .add_systems(OnEnter(InGameState::StageClear), spawn_stage_clear_view.after(award_stage_bonus).after(unlock_next_stage))
+            .add_systems(OnExit(InGameState::StageClear), despawn_stage_clear_view)
+            .add_systems(OnEnter(InGameState::Summary), spawn_summary_view)
+            .add_systems(OnExit(InGameState::Summary), despawn_summary_view)
+            .add_systems(Update,
+                 (
+                     update_lives_view,
+                     update_remaining_time_view,
+                     update_combo_view,
+                     spawn_score_popups,
+                     update_score_popups,
+                     (
+                         update_score_view,
+                         update_active_effects_view,
                      ).run_if(in_state(InGameState::Play)),
                      (
                          check_pause_interactions,
                      ).run_if(in_state(InGameState::Pause)),
+                     (
+                         check_stage_clear_interactions,
+                     ).run_if(in_state(InGameState::StageClear)),
                      (
                          check_summary_interactions,
+                         check_name_entry.run_if(resource_exists::<PendingHighScore>()),
                      ).run_if(in_state(InGameState::Summary)),
                      (
-                         check_menu_condition,
-                         check_restart_condition,
-                         check_toggle_pause_condition,
-                         check_summary_condition,
-                     ),
+                         update_replay_view,
+                         check_replay_view_interactions,
+                     ).run_if(resource_exists::<ReplayPlayback>()),
                  ).run_if(in_state(AppState::InGame)),
             );
     }
@@ -127,7 +276,7 @@ fn start_up(
     mut next_state: ResMut<NextState<InGameState>>,
 )
 {
-    next_state.set(InGameState::Preparation);
+    next_state.set(InGameState::Loading);
 }
 
 fn clean_up(
@@ -143,24 +292,36 @@ fn clean_up(
     commands.insert_resource(BrickGhost::default());
     commands.insert_resource(PaddleSize::default());
     commands.insert_resource(PaddleSpeed::default());
+    commands.insert_resource(PaddleDeflection::default());
+    commands.insert_resource(ActiveEffects::default());
+    commands.insert_resource(Lives::default());
+    commands.insert_resource(RemainingTime::default());
+    commands.insert_resource(WallAdvanceTimer::default());
+    commands.insert_resource(RunStatistics::default());
+    commands.insert_resource(Combo::default());
+    commands.remove_resource::<GameOverReason>();
 }
 
 fn check_preparation_end_condition(
-    keyboard_input: Res<Input<KeyCode>>,
-    mouse_input: Res<Input<MouseButton>>,
+    player_input: Res<PlayerInput>,
     mut next_state: ResMut<NextState<InGameState>>,
 )
 {
-    if let Some(key) = keyboard_input.get_just_pressed().next() {
-        if *key != KeyCode::Left && *key != KeyCode::Right {
-            next_state.set(InGameState::Play);
-        }
-    }
-    else if mouse_input.get_just_pressed().next() != None {
+    if player_input.launch {
         next_state.set(InGameState::Play);
     }
 }
 
+fn start_replay_from_launch(
+    replay_playback: Option<Res<ReplayPlayback>>,
+    mut next_state: ResMut<NextState<AppState>>,
+)
+{
+    if replay_playback.is_some() {
+        next_state.set(AppState::InGame);
+    }
+}
+
 fn check_menu_condition(
     mut menu_requested_events: EventReader<MenuRequested>,
     mut next_state: ResMut<NextState<AppState>>
@@ -176,6 +337,7 @@ fn check_menu_condition(
 }
 
 fn check_restart_condition(
+    mut commands: Commands,
     mut restart_requested_events: EventReader<RestartRequested>,
     mut next_state: ResMut<NextState<AppState>>
 )
@@ -186,6 +348,7 @@ fn check_restart_condition(
     }
 
     restart_requested_events.clear();
+    commands.remove_resource::<ReplayPlayback>();
     next_state.set(AppState::RestartInGame);
 }
 
@@ -197,21 +360,34 @@ fn continue_restart_game(
 }
 
 fn check_summary_condition(
+    mut commands: Commands,
     mut last_ball_destroyed_events: EventReader<LastBallDestroyed>,
+    mut level_completed_events: EventReader<LevelCompleted>,
+    game_mode: Res<GameMode>,
     mut next_state: ResMut<NextState<InGameState>>,
 )
 {
-    if last_ball_destroyed_events.is_empty()
+    if last_ball_destroyed_events.is_empty() && level_completed_events.is_empty()
     {
         return;
     }
 
+    let level_completed = !level_completed_events.is_empty();
     last_ball_destroyed_events.clear();
+    level_completed_events.clear();
+
+    // A cleared campaign stage is tallied up before the campaign goes on.
+    if level_completed && matches!(*game_mode, GameMode::Campaign { .. }) {
+        next_state.set(InGameState::StageClear);
+        return;
+    }
+
+    commands.insert_resource(if level_completed { GameOverReason::LevelCompleted } else { GameOverReason::OutOfLives });
     next_state.set(InGameState::Summary);
 }
 
 fn check_toggle_pause_condition(
-    input: Res<Input<KeyCode>>,
+    action_input: ActionInput,
     current_state: Res<State<InGameState>>,
     mut next_state: ResMut<NextState<InGameState>>,
     mut toggle_pause_requested_events: EventReader<TogglePauseRequested>,
@@ -225,7 +401,7 @@ fn check_toggle_pause_condition(
         toggle_pause_requested_events.clear();
 
     }
-    if input.just_pressed(KeyCode::Escape) {
+    if action_input.just_pressed(Action::Pause) {
         toggle = true;
     }
 
@@ -233,45 +409,10 @@ fn check_toggle_pause_condition(
         return;
     }
 
-    if *current_state.get() == InGameState::Play {
-        next_state.set(InGameState::Pause);
-    }
-    else {
-        next_state.set(InGameState::Play);
+    // Only a running game can be paused, pausing the preparation or the summary would start the game.
+    match current_state.get() {
+        InGameState::Play => next_state.set(InGameState::Pause),
+        InGameState::Pause => next_state.set(InGameState::Play),
+        _ => {}
     }
 }
-
-pub fn test_settings(
-    input: Res<Input<KeyCode>>,
-    mut ball_size: ResMut<BallSize>,
-    mut ball_speed: ResMut<BallSpeed>,
-    mut brick_ghost: ResMut<BrickGhost>,
-    mut paddle_size: ResMut<PaddleSize>,
-    mut paddle_speed: ResMut<PaddleSpeed>,
-)
-{
-    let value =
-        if input.just_pressed(KeyCode::Q) { -1 }
-        else if input.just_pressed(KeyCode::E) { 1 }
-        else { 0 };
-
-    if value == 0 {
-        return;
-    }
-
-    if input.pressed(KeyCode::Key1) {
-        paddle_size.change_points(value);
-    }
-    if input.pressed(KeyCode::Key2) {
-        paddle_speed.change_points(value);
-    }
-    if input.pressed(KeyCode::Key3) {
-        ball_size.change_points(value);
-    }
-    if input.pressed(KeyCode::Key4) {
-        ball_speed.change_points(value);
-    }
-    if input.pressed(KeyCode::Key5) {
-        brick_ghost.set_enabled(value > 0);
-    }
-}
\ No newline at end of file
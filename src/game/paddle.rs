@@ -1,9 +1,14 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use crate::game::textures::Textures;
 use bevy::sprite::Anchor;
 use super::resources::{PaddleSize, PaddleSpeed};
 use super::collider::BoxCollider;
+use super::interpolation::InterpolatedTransform;
 use super::ball::{ BallObstacle, BallObstacleType };
 use crate::WINDOW_USABLE_WORLD_WIDTH;
+use crate::game::player_input::PlayerInput;
+use crate::game::power_ups::StuckBall;
 
 pub const PADDLE_WIDTH: f32 = 104.0;
 const PADDLE_HALF_WIDTH: f32 = PADDLE_WIDTH / 2.0;
@@ -16,8 +21,30 @@ pub enum PaddleSegmentType {
     Right,
 }
 
-#[derive(Component)]
-pub struct Paddle;
+#[derive(Component, Default)]
+pub struct Paddle {
+    /// How fast the paddle moved in the latest step, gives the ball spin.
+    pub velocity_x: f32,
+    /// Fires lasers on launch.
+    pub laser: bool,
+    /// Seconds until the next lasers can be fired.
+    pub laser_cooldown: f32,
+    /// Catches balls landing on it, they stay until launched again.
+    pub sticky: bool,
+}
+
+/// Tells whether the paddle touched a ball in the latest step, a ball held by the sticky paddle counts as well.
+#[derive(SystemParam)]
+pub struct PaddleContact<'w, 's> {
+    paddle_query: Query<'w, 's, &'static BallObstacle, With<Paddle>>,
+    stuck_ball_query: Query<'w, 's, (), With<StuckBall>>,
+}
+
+impl PaddleContact<'_, '_> {
+    pub fn is_touching(&self) -> bool {
+        self.paddle_query.iter().any(|obstacle| obstacle.hit_by.is_some()) || !self.stuck_ball_query.is_empty()
+    }
+}
 
 #[derive(Component)]
 pub struct PaddleSegment {
@@ -26,22 +53,23 @@ pub struct PaddleSegment {
 
 pub fn spawn_paddle(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    textures: Textures,
     paddle_size: Res<PaddleSize>,
 )
 {
     let paddle_width = paddle_size.get_width();
 
     commands.spawn((
-        Paddle,
+        Paddle::default(),
         SpatialBundle {
             transform: Transform::from_xyz(WINDOW_USABLE_WORLD_WIDTH / 2.0, PADDLE_HEIGHT / 2.0, 0.0),
             ..default()
         },
-        BallObstacle::new(BallObstacleType::Centric),
+        BallObstacle::new(BallObstacleType::Deflecting),
         BoxCollider {
             extends: Vec2::new(paddle_width / 2.0, PADDLE_HALF_HEIGHT),
-        }))
+        },
+        InterpolatedTransform::default()))
         .with_children(|builder| {
             builder.spawn((
                 SpriteBundle {
@@ -49,7 +77,7 @@ pub fn spawn_paddle(
                         scale: get_center_paddle_segment_local_scale(paddle_width),
                         ..default()
                     },
-                    texture: asset_server.load("sprites/paddleBlue.png"),
+                    texture: textures.load("sprites/paddleBlue.png"),
                     sprite: Sprite {
                         rect: Some(Rect {
                             min: Vec2::new(PADDLE_WIDTH * 0.25, 0.0),
@@ -70,7 +98,7 @@ pub fn spawn_paddle(
                         translation: get_left_paddle_segment_local_position(paddle_width),
                         ..default()
                     },
-                    texture: asset_server.load("sprites/paddleBlue.png"),
+                    texture: textures.load("sprites/paddleBlue.png"),
                     sprite: Sprite {
                         anchor: Anchor::CenterRight,
                         rect: Some(Rect {
@@ -92,7 +120,7 @@ pub fn spawn_paddle(
                         translation: get_right_paddle_segment_local_position(paddle_width),
                         ..default()
                     },
-                    texture: asset_server.load("sprites/paddleBlue.png"),
+                    texture: textures.load("sprites/paddleBlue.png"),
                     sprite: Sprite {
                         anchor: Anchor::CenterLeft,
                         rect: Some(Rect {
@@ -121,37 +149,35 @@ pub fn despawn_paddles(
 }
 
 pub fn move_paddle(
-    input: Res<Input<KeyCode>>,
-    mut paddle_query: Query<(&mut Transform, &BoxCollider), With<Paddle>>,
+    player_input: Res<PlayerInput>,
+    mut paddle_query: Query<(&mut Transform, &mut Paddle, &BoxCollider)>,
     time: Res<Time>,
     paddle_speed: Res<PaddleSpeed>,
 )
 {
-    let mut value: f32 = 0.0;
-    if input.pressed(KeyCode::Left) {
-        value -= 1.0;
-    }
-    if input.pressed(KeyCode::Right) {
-        value += 1.0;
-    }
-
-    if value != 0.0 {
-        if let Ok((mut transform, obstacle)) = paddle_query.get_single_mut() {
-            let mut position = transform.translation;
-            position.x += value * paddle_speed.get_speed() * time.delta_seconds();
-
-            let paddle_half_width = obstacle.extends.x;
-            let min_x = paddle_half_width;
-            let max_x = WINDOW_USABLE_WORLD_WIDTH - paddle_half_width;
-            if position.x < min_x {
-                position.x = min_x;
-            }
-            else if position.x > max_x {
-                position.x = max_x;
-            }
-            transform.translation = position;
+    let Ok((mut transform, mut paddle, collider)) = paddle_query.get_single_mut() else {
+        return;
+    };
+
+    let max_distance = paddle_speed.get_speed() * time.delta_seconds();
+    let movement = player_input.get_movement();
+    let distance = match player_input.target_x {
+        // The pointer is followed as fast as the paddle can go, never faster, unless a key or the stick moves the paddle.
+        Some(target_x) if movement == 0. => {
+            let target_x = collider.clamp_center_x(target_x, 0., WINDOW_USABLE_WORLD_WIDTH);
+            (target_x - transform.translation.x).clamp(-max_distance, max_distance)
         }
+        _ => movement * max_distance,
+    };
+
+    let previous_x = transform.translation.x;
+    if distance != 0.0 {
+        let position_x = transform.translation.x + distance;
+        transform.translation.x = collider.clamp_center_x(position_x, 0., WINDOW_USABLE_WORLD_WIDTH);
     }
+
+    let delta_seconds = time.delta_seconds();
+    paddle.velocity_x = if delta_seconds > 0. { (transform.translation.x - previous_x) / delta_seconds } else { 0. };
 }
 
 pub fn keep_paddle_synced_with_settings(
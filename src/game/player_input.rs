@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy::input::touch::Touches;
+use bevy::window::PrimaryWindow;
+use crate::common::actions::{Action, ActionInput};
+use crate::common::settings::{ControlScheme, Settings};
+
+/// The stick is rounded to this many steps per direction, so a replay can merge the frames a shaky hand barely changes.
+const STICK_STEPS: f32 = 64.;
+
+/// Everything a player can do to influence a game, gathered once per frame.
+///
+/// Gameplay systems read this instead of the raw input devices, so a recorded session can be fed back into them.
+#[derive(Resource, Clone, Copy, Default, PartialEq)]
+pub struct PlayerInput {
+    pub move_left: bool,
+    pub move_right: bool,
+    /// Pressed since the latest step, kept until a step used it as frames do not always run exactly one step.
+    pub launch: bool,
+    /// How far a gamepad stick is pushed, from -1 (left) to 1 (right).
+    pub stick_x: f32,
+    /// Where the pointer wants the paddle to be, in world coordinates. Only followed while neither the move keys nor the stick are used.
+    pub target_x: Option<f32>,
+}
+
+impl PlayerInput {
+    const MOVE_LEFT_BIT: u8 = 1 << 0;
+    const MOVE_RIGHT_BIT: u8 = 1 << 1;
+    const LAUNCH_BIT: u8 = 1 << 2;
+    const TARGET_BIT: u8 = 1 << 3;
+    const STICK_BIT: u8 = 1 << 4;
+
+    pub fn get_movement(&self) -> f32 {
+        let mut value = 0.;
+        if self.move_left {
+            value -= 1.;
+        }
+        if self.move_right {
+            value += 1.;
+        }
+        (value + self.stick_x).clamp(-1., 1.)
+    }
+
+    pub fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        if self.move_left {
+            bits |= Self::MOVE_LEFT_BIT;
+        }
+        if self.move_right {
+            bits |= Self::MOVE_RIGHT_BIT;
+        }
+        if self.launch {
+            bits |= Self::LAUNCH_BIT;
+        }
+        if self.target_x.is_some() {
+            bits |= Self::TARGET_BIT;
+        }
+        if self.stick_x != 0. {
+            bits |= Self::STICK_BIT;
+        }
+        bits
+    }
+
+    /// Restores everything but the stick and the target, which are stored next to the bits when `has_stick_bit` and `has_target_bit` say so.
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            move_left: bits & Self::MOVE_LEFT_BIT != 0,
+            move_right: bits & Self::MOVE_RIGHT_BIT != 0,
+            launch: bits & Self::LAUNCH_BIT != 0,
+            stick_x: 0.,
+            target_x: None,
+        }
+    }
+
+    pub fn has_stick_bit(bits: u8) -> bool {
+        bits & Self::STICK_BIT != 0
+    }
+
+    pub fn has_target_bit(bits: u8) -> bool {
+        bits & Self::TARGET_BIT != 0
+    }
+}
+
+pub fn read_player_input(
+    action_input: ActionInput,
+    mouse_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    settings: Res<Settings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut player_input: ResMut<PlayerInput>,
+)
+{
+    let target_x = match settings.control_scheme {
+        ControlScheme::Keyboard => None,
+        ControlScheme::Pointer => get_pointer_position(&touches, &window_query, &camera_query).map(|position| position.x),
+    };
+
+    *player_input = PlayerInput {
+        move_left: action_input.pressed(Action::MoveLeft),
+        move_right: action_input.pressed(Action::MoveRight),
+        launch: player_input.launch
+            || action_input.just_pressed(Action::Launch)
+            || mouse_input.get_just_pressed().next().is_some()
+            || touches.any_just_pressed(),
+        stick_x: (action_input.get_gamepad_axis(GamepadAxisType::LeftStickX) * STICK_STEPS).round() / STICK_STEPS,
+        target_x,
+    };
+}
+
+/// The world position of the finger on the screen, or of the mouse cursor when there is none.
+fn get_pointer_position(
+    touches: &Touches,
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2>
+{
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+    let screen_position = match touches.iter().next() {
+        Some(touch) => touch.position(),
+        None => window_query.get_single().ok()?.cursor_position()?,
+    };
+    camera.viewport_to_world_2d(camera_transform, screen_position)
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use crate::{WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT};
+use crate::game::ball::Ball;
+use crate::game::brick::Brick;
+use crate::game::broad_phase::CollisionGrid;
+use crate::game::collider::BoxCollider;
+use crate::game::interpolation::InterpolatedTransform;
+use crate::game::paddle::Paddle;
+use crate::game::player_input::PlayerInput;
+use crate::game::resources::PaddleDeflection;
+use crate::game::trajectory::BallTrajectory;
+use super::ball::{BallObstacle, BallObstacleType};
+
+const LASER_SPEED: f32 = 900.;
+const LASER_SIZE: Vec2 = Vec2::new(4., 16.);
+const LASER_COLOR: Color = Color::rgb(1., 0.25, 0.2);
+const LASER_COOLDOWN_SECONDS: f32 = 0.3;
+/// How far in from the ends of the paddle the lasers are fired.
+const LASER_INSET: f32 = 12.;
+const SHIELD_HEIGHT: f32 = 6.;
+const SHIELD_COLOR: Color = Color::rgba(0.3, 0.6, 1., 0.8);
+
+/// A shot fired by a laser paddle, flies straight up and hits the first brick in its way.
+#[derive(Component)]
+pub struct Laser;
+
+/// Spans the bottom of the playfield and bounces a single ball back before breaking.
+#[derive(Component)]
+pub struct Shield;
+
+/// A ball caught by a sticky paddle, it rides along until the player launches it again.
+#[derive(Component)]
+pub struct StuckBall {
+    /// Where the ball sits, relative to the paddle center.
+    pub offset_x: f32,
+}
+
+pub fn spawn_shield(
+    commands: &mut Commands,
+)
+{
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_xyz(WINDOW_USABLE_WORLD_WIDTH / 2., SHIELD_HEIGHT / 2., 0.),
+            sprite: Sprite {
+                color: SHIELD_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_USABLE_WORLD_WIDTH, SHIELD_HEIGHT)),
+                ..default()
+            },
+            ..default()
+        },
+        Shield,
+        BallObstacle::new(BallObstacleType::Natural),
+        BoxCollider {
+            extends: Vec2::new(WINDOW_USABLE_WORLD_WIDTH / 2., SHIELD_HEIGHT / 2.),
+        },
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+pub fn despawn_power_ups(
+    mut commands: Commands,
+    power_up_query: Query<Entity, Or<(With<Laser>, With<Shield>)>>,
+)
+{
+    for power_up in power_up_query.iter() {
+        commands.entity(power_up).despawn();
+    }
+}
+
+pub fn break_shields_on_hit(
+    mut commands: Commands,
+    shield_query: Query<(Entity, &BallObstacle), With<Shield>>,
+)
+{
+    for (entity, obstacle) in shield_query.iter() {
+        if obstacle.hit_by.is_some() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn fire_lasers(
+    mut commands: Commands,
+    player_input: Res<PlayerInput>,
+    mut paddle_query: Query<(&Transform, &BoxCollider, &mut Paddle)>,
+    time: Res<Time>,
+)
+{
+    let Ok((paddle_transform, paddle_collider, mut paddle)) = paddle_query.get_single_mut() else {
+        return;
+    };
+
+    paddle.laser_cooldown = (paddle.laser_cooldown - time.delta_seconds()).max(0.);
+    if !paddle.laser || !player_input.launch || paddle.laser_cooldown > 0. {
+        return;
+    }
+    paddle.laser_cooldown = LASER_COOLDOWN_SECONDS;
+
+    let y = paddle_transform.translation.y + paddle_collider.extends.y + LASER_SIZE.y / 2.;
+    let offset_x = (paddle_collider.extends.x - LASER_INSET).max(0.);
+    for x in [paddle_transform.translation.x - offset_x, paddle_transform.translation.x + offset_x] {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_xyz(x, y, 0.),
+                sprite: Sprite {
+                    color: LASER_COLOR,
+                    custom_size: Some(LASER_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+            Laser,
+            InterpolatedTransform::default(),
+        ));
+    }
+}
+
+pub fn move_lasers(
+    mut commands: Commands,
+    mut laser_query: Query<(Entity, &mut Transform), With<Laser>>,
+    mut brick_query: Query<&mut Brick>,
+    collision_grid: Res<CollisionGrid>,
+    time: Res<Time>,
+)
+{
+    let radius = LASER_SIZE.x / 2.;
+    let motion = Vec2::new(0., LASER_SPEED * time.delta_seconds());
+    let mut candidates = Vec::new();
+
+    for (entity, mut transform) in laser_query.iter_mut() {
+        let tip = transform.translation.xy() + Vec2::new(0., LASER_SIZE.y / 2.);
+        collision_grid.find_candidates(tip - radius, tip + motion + radius, &mut candidates);
+
+        let first_hit = candidates
+            .iter()
+            .map(|index| &collision_grid.get_entries()[*index])
+            .filter(|entry| brick_query.contains(entry.entity))
+            .filter_map(|entry| {
+                let hit = BoxCollider::sweep_circle(entry.center, entry.extents, tip, motion, radius)?;
+                Some((hit.time, entry.entity))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((_, brick_entity)) = first_hit {
+            brick_query.get_mut(brick_entity).unwrap().hit_by_laser();
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation += motion.extend(0.);
+        if transform.translation.y - LASER_SIZE.y / 2. > WINDOW_WORLD_HEIGHT {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Keeps caught balls on the paddle and sends them off again on launch, at the angle their spot on the paddle gives.
+pub fn keep_stuck_balls_on_paddle(
+    mut commands: Commands,
+    player_input: Res<PlayerInput>,
+    paddle_query: Query<(&Transform, &BoxCollider, &Paddle)>,
+    mut ball_query: Query<(Entity, &mut Transform, &mut Ball, &mut BallTrajectory, &StuckBall), Without<Paddle>>,
+    paddle_deflection: Res<PaddleDeflection>,
+)
+{
+    let Ok((paddle_transform, paddle_collider, paddle)) = paddle_query.get_single() else {
+        return;
+    };
+
+    for (entity, mut ball_transform, mut ball, mut trajectory, stuck_ball) in ball_query.iter_mut() {
+        let offset_x = stuck_ball.offset_x.clamp(-paddle_collider.extends.x, paddle_collider.extends.x);
+        ball_transform.translation.x = paddle_transform.translation.x + offset_x;
+        ball_transform.translation.y = paddle_transform.translation.y + paddle_collider.extends.y + ball.size.get_radius();
+
+        if player_input.launch {
+            let hit_offset = if paddle_collider.extends.x > 0. { offset_x / paddle_collider.extends.x } else { 0. };
+            let direction = paddle_deflection.get_direction(hit_offset, paddle.velocity_x, ball.speed.get_speed());
+            ball.direction = direction.extend(0.);
+            trajectory.clear();
+            commands.entity(entity).remove::<StuckBall>();
+        }
+    }
+}
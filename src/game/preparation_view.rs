@@ -1,5 +1,7 @@
 use bevy::asset::AssetServer;
 use bevy::prelude::{BuildChildren, Commands, Component, DespawnRecursiveExt, Entity, Query, Res, With};
+use crate::common::actions::{Action, get_key_name};
+use crate::common::settings::{ControlScheme, Settings};
 use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_header};
 
 #[derive(Component)]
@@ -8,18 +10,25 @@ pub struct PreparationView;
 pub fn spawn_preparation_view(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
 )
 {
+    let key_bindings = &settings.key_bindings;
+
     commands.spawn(
         (
             PreparationView {},
             get_full_screen_menu_node_bundle(),
         )
     ).with_children(|parent| {
-        spawn_full_screen_menu_header(parent, &asset_server, "Use arrows to move the paddle.");
+        let move_keys = format!("{} and {}", get_key_name(key_bindings.get(Action::MoveLeft)), get_key_name(key_bindings.get(Action::MoveRight)));
+        match settings.control_scheme {
+            ControlScheme::Keyboard => spawn_full_screen_menu_header(parent, &asset_server, format!("Use {} to move the paddle.", move_keys)),
+            ControlScheme::Pointer => spawn_full_screen_menu_header(parent, &asset_server, format!("Move the mouse, drag or use {} to move the paddle.", move_keys)),
+        }
         spawn_full_screen_menu_header(parent, &asset_server, "Collect squares to earn points.");
-        spawn_full_screen_menu_header(parent, &asset_server, "Press any key to start.");
-        spawn_full_screen_menu_header(parent, &asset_server, "(Optionally, press esc to pause.)");
+        spawn_full_screen_menu_header(parent, &asset_server, format!("Press {}, click or tap to start.", get_key_name(key_bindings.get(Action::Launch))));
+        spawn_full_screen_menu_header(parent, &asset_server, format!("(Optionally, press {} to pause.)", get_key_name(key_bindings.get(Action::Pause))));
     });
 }
 
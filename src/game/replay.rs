@@ -0,0 +1,725 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use thiserror::Error;
+use crate::AppState;
+use crate::common::storage::get_data_dir;
+use crate::game::campaign::{CAMPAIGN_STAGE_PATHS, CarryOver};
+use crate::game::events::ReplayRequested;
+use crate::game::game_mode::GameMode;
+use crate::game::InGameState;
+use crate::game::interpolation::{interpolate_transforms, restore_simulated_transforms, store_previous_transforms};
+use crate::game::level::CurrentLevel;
+use crate::game::player_input::PlayerInput;
+use crate::game::resources::GameRng;
+
+const REPLAY_MAGIC: &[u8; 4] = b"BBRP";
+const REPLAY_VERSION: u8 = 1;
+const LAST_REPLAY_FILE_NAME: &str = "last.replay";
+/// Longer level paths only come from broken files.
+const MAX_LEVEL_PATH_LENGTH: usize = 4096;
+/// Eight hours of steps, recording stops past it and longer replays only come from broken files.
+const MAX_REPLAY_FRAMES: usize = 8 * 60 * 60 * 120;
+const MAX_SEEK_STEPS_PER_FRAME: usize = 240;
+pub const PLAYBACK_SPEEDS: &[usize] = &[1, 2, 4];
+/// The simulated time of one step, the same on every machine whatever its frame rate.
+pub const GAME_STEP_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 120);
+/// Frame time beyond this many steps is dropped, so a long hitch slows the game down instead of freezing it.
+const MAX_LIVE_STEPS_PER_FRAME: usize = 8;
+
+/// Runs the gameplay systems for one simulation step.
+///
+/// Live games run as many steps of [`GAME_STEP_DURATION`] as fit in the frame time,
+/// replays run the recorded steps as fast as the playback speed asks for.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameStep;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct ReplayFrame {
+    pub delta: Duration,
+    pub input: PlayerInput,
+}
+
+/// Everything needed to play a session again: its seed, its level and the input of every simulation step.
+#[derive(Clone)]
+pub struct Replay {
+    pub seed: u64,
+    pub level_path: String,
+    pub mode: GameMode,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("could not read the replay file: {0}")]
+    Io(#[from] io::Error),
+    #[error("the file is not a replay")]
+    NotAReplay,
+    #[error("replay version {0} is not supported")]
+    UnsupportedVersion(u8),
+    #[error("the replay file is corrupted")]
+    Corrupted,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    fn read(reader: &mut impl Read) -> Result<Self, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != REPLAY_MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+
+        let version = read_u8(reader)?;
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = read_varint(reader)?;
+        let level_path_length = read_varint(reader)? as usize;
+        if level_path_length > MAX_LEVEL_PATH_LENGTH {
+            return Err(ReplayError::Corrupted);
+        }
+        let mut level_path = vec![0; level_path_length];
+        reader.read_exact(&mut level_path)?;
+        let level_path = String::from_utf8(level_path).map_err(|_| ReplayError::Corrupted)?;
+
+        let mode = read_game_mode(reader)?;
+
+        // Frames are stored as runs of identical frames.
+        let number_of_runs = read_varint(reader)?;
+        let mut frames = Vec::new();
+        for _ in 0..number_of_runs {
+            let run_length = read_varint(reader)? as usize;
+            if run_length > MAX_REPLAY_FRAMES - frames.len() {
+                return Err(ReplayError::Corrupted);
+            }
+            let delta = Duration::from_nanos(read_varint(reader)?);
+            let bits = read_u8(reader)?;
+            let mut input = PlayerInput::from_bits(bits);
+            if PlayerInput::has_stick_bit(bits) {
+                input.stick_x = read_f32(reader)?;
+            }
+            if PlayerInput::has_target_bit(bits) {
+                input.target_x = Some(read_f32(reader)?);
+            }
+            let frame = ReplayFrame {
+                delta,
+                input,
+            };
+            frames.extend(std::iter::repeat_n(frame, run_length));
+        }
+
+        Ok(Self {
+            seed,
+            level_path,
+            mode,
+            frames,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(REPLAY_MAGIC)?;
+        writer.write_all(&[REPLAY_VERSION])?;
+        write_varint(writer, self.seed)?;
+        write_varint(writer, self.level_path.len() as u64)?;
+        writer.write_all(self.level_path.as_bytes())?;
+        write_game_mode(writer, self.mode)?;
+
+        let mut runs: Vec<(usize, ReplayFrame)> = Vec::new();
+        for frame in self.frames.iter() {
+            match runs.last_mut() {
+                Some((run_length, run_frame)) if run_frame == frame => *run_length += 1,
+                _ => runs.push((1, *frame)),
+            }
+        }
+
+        write_varint(writer, runs.len() as u64)?;
+        for (run_length, frame) in runs {
+            write_varint(writer, run_length as u64)?;
+            write_varint(writer, frame.delta.as_nanos() as u64)?;
+            writer.write_all(&[frame.input.to_bits()])?;
+            if frame.input.stick_x != 0. {
+                writer.write_all(&frame.input.stick_x.to_le_bytes())?;
+            }
+            if let Some(target_x) = frame.input.target_x {
+                writer.write_all(&target_x.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_game_mode(reader: &mut impl Read) -> Result<GameMode, ReplayError> {
+    match read_u8(reader)? {
+        0 => Ok(GameMode::Endless),
+        1 => Ok(GameMode::TimeAttack),
+        2 => {
+            let stage = read_varint(reader)? as usize;
+            if stage >= CAMPAIGN_STAGE_PATHS.len() {
+                return Err(ReplayError::Corrupted);
+            }
+            Ok(GameMode::Campaign {
+                stage,
+                carry_over: read_carry_over(reader)?,
+            })
+        }
+        3 => Ok(GameMode::AdvancingWall),
+        _ => Err(ReplayError::Corrupted),
+    }
+}
+
+fn write_game_mode(writer: &mut impl Write, mode: GameMode) -> io::Result<()> {
+    match mode {
+        GameMode::Endless => writer.write_all(&[0]),
+        GameMode::TimeAttack => writer.write_all(&[1]),
+        GameMode::AdvancingWall => writer.write_all(&[3]),
+        GameMode::Campaign { stage, carry_over } => {
+            writer.write_all(&[2])?;
+            write_varint(writer, stage as u64)?;
+            write_carry_over(writer, carry_over)
+        }
+    }
+}
+
+fn read_carry_over(reader: &mut impl Read) -> Result<Option<CarryOver>, ReplayError> {
+    if read_u8(reader)? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(CarryOver {
+        score: read_varint(reader)? as usize,
+        lives: read_varint(reader)? as usize,
+    }))
+}
+
+fn write_carry_over(writer: &mut impl Write, carry_over: Option<CarryOver>) -> io::Result<()> {
+    match carry_over {
+        Some(carry_over) => {
+            writer.write_all(&[1])?;
+            write_varint(writer, carry_over.score as u64)?;
+            write_varint(writer, carry_over.lives as u64)
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn read_varint(reader: &mut impl Read) -> Result<u64, ReplayError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(reader)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(ReplayError::Corrupted)
+}
+
+fn write_varint(writer: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+pub fn get_last_replay_path() -> Option<PathBuf> {
+    get_data_dir().map(|dir| dir.join(LAST_REPLAY_FILE_NAME))
+}
+
+/// Saves the replay of the latest live session, so it can be watched again from the summary or the command line.
+pub fn save_last_replay(replay: &Replay) {
+    let Some(path) = get_last_replay_path() else {
+        warn!("Could not find a data directory, the replay will not be saved.");
+        return;
+    };
+
+    if let Err(error) = replay.save(&path) {
+        warn!("Could not save the replay to '{}': {}", path.display(), error);
+    }
+}
+
+/// Collects the frames of the live session in progress.
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    replay: Option<Replay>,
+}
+
+impl ReplayRecorder {
+    pub fn finish(&mut self) -> Option<Replay> {
+        self.replay.take()
+    }
+}
+
+/// The replay of the latest live session.
+#[derive(Resource)]
+pub struct LastReplay(pub Replay);
+
+/// Present while a replay is being watched instead of a live game.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    next_frame: usize,
+    clock: Time,
+    /// Frame time not yet used up by recorded steps.
+    lag: Duration,
+    speed: usize,
+    paused: bool,
+    seek_target: Option<usize>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_frame: 0,
+            clock: Time::default(),
+            lag: Duration::ZERO,
+            speed: PLAYBACK_SPEEDS[0],
+            paused: false,
+            seek_target: None,
+        }
+    }
+
+    pub fn get_replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn get_frame(&self) -> usize {
+        self.next_frame
+    }
+
+    pub fn get_number_of_frames(&self) -> usize {
+        self.replay.frames.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_frame >= self.replay.frames.len()
+    }
+
+    pub fn get_speed(&self) -> usize {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: usize) {
+        self.speed = speed;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Jumps to the given frame. Going back in time restarts the game and plays it again up to that frame.
+    pub fn seek(&mut self, frame: usize) {
+        self.seek_target = Some(frame.min(self.replay.frames.len()));
+    }
+
+    pub fn is_seeking(&self) -> bool {
+        self.seek_target.is_some()
+    }
+
+    fn needs_rewind(&self) -> bool {
+        self.seek_target.is_some_and(|target| target < self.next_frame)
+    }
+
+    fn rewind(&mut self) {
+        self.next_frame = 0;
+        self.clock = Time::default();
+        self.lag = Duration::ZERO;
+    }
+
+    /// Adds the frame time, sped up by the playback speed, and returns how many steps may run at most this frame.
+    fn start_frame(&mut self, frame_delta: Duration) -> usize {
+        match self.seek_target {
+            Some(target) if target > self.next_frame => (target - self.next_frame).min(MAX_SEEK_STEPS_PER_FRAME),
+            _ if self.paused => 0,
+            _ => {
+                let max_lag = GAME_STEP_DURATION * (MAX_LIVE_STEPS_PER_FRAME * self.speed) as u32;
+                self.lag = (self.lag + frame_delta * self.speed as u32).min(max_lag);
+                MAX_SEEK_STEPS_PER_FRAME
+            }
+        }
+    }
+
+    /// How far the frame time got towards the next recorded step.
+    fn get_interpolation_alpha(&self) -> f32 {
+        match self.replay.frames.get(self.next_frame) {
+            Some(frame) if !frame.delta.is_zero() => (self.lag.as_secs_f32() / frame.delta.as_secs_f32()).min(1.),
+            _ => 1.,
+        }
+    }
+
+    fn advance(&mut self) -> Option<(ReplayFrame, Time)> {
+        let frame = *self.replay.frames.get(self.next_frame)?;
+        if self.seek_target.is_none() {
+            // Played in real time, a step waits until enough frame time came together.
+            if frame.delta > self.lag {
+                return None;
+            }
+            self.lag -= frame.delta;
+        }
+        self.next_frame += 1;
+        self.clock.advance_by(frame.delta);
+
+        if self.seek_target.is_some_and(|target| self.next_frame >= target) {
+            self.seek_target = None;
+            self.lag = Duration::ZERO;
+        }
+
+        Some((frame, self.clock))
+    }
+}
+
+/// Turns the frame time of a live game into steps of [`GAME_STEP_DURATION`].
+#[derive(Resource, Default)]
+pub struct GameStepClock {
+    clock: Time,
+    /// Frame time not yet used up by steps.
+    lag: Duration,
+}
+
+pub fn run_game_steps(world: &mut World) {
+    restore_simulated_transforms(world);
+
+    let alpha = if world.contains_resource::<ReplayPlayback>() {
+        run_replay_steps(world)
+    }
+    else {
+        run_live_steps(world)
+    };
+
+    let virtual_time = world.resource::<Time<Virtual>>().as_generic();
+    *world.resource_mut::<Time>() = virtual_time;
+
+    interpolate_transforms(world, alpha);
+}
+
+fn is_simulating(world: &World) -> bool {
+    matches!(world.resource::<State<InGameState>>().get(), InGameState::Preparation | InGameState::Play)
+}
+
+/// Runs one step with the given simulated time, returns whether the game waits for a state transition.
+fn run_game_step(world: &mut World, time: Time) -> bool {
+    *world.resource_mut::<Time>() = time;
+    store_previous_transforms(world);
+    world.run_schedule(GameStep);
+
+    // State transitions only happen between frames, the next step has to wait for it.
+    world.resource::<NextState<InGameState>>().0.is_some()
+}
+
+/// Returns how far the frame time got towards the next step.
+fn run_live_steps(world: &mut World) -> f32 {
+    world.resource_scope(|world, mut game_step_clock: Mut<GameStepClock>| {
+        // The simulation stands still while the game is paused or over, and catches up on nothing afterwards.
+        if is_simulating(world) {
+            let lag = game_step_clock.lag + world.resource::<Time<Virtual>>().delta();
+            game_step_clock.lag = lag.min(GAME_STEP_DURATION * MAX_LIVE_STEPS_PER_FRAME as u32);
+        }
+        else {
+            // A click on a button of the pause or summary screen must not launch once the game goes on.
+            world.resource_mut::<PlayerInput>().launch = false;
+        }
+
+        while is_simulating(world) && game_step_clock.lag >= GAME_STEP_DURATION {
+            game_step_clock.lag -= GAME_STEP_DURATION;
+            game_step_clock.clock.advance_by(GAME_STEP_DURATION);
+            let waiting = run_game_step(world, game_step_clock.clock);
+
+            let frame = ReplayFrame {
+                delta: GAME_STEP_DURATION,
+                input: *world.resource::<PlayerInput>(),
+            };
+            let mut replay_recorder = world.resource_mut::<ReplayRecorder>();
+            if let Some(replay) = &mut replay_recorder.replay {
+                if replay.frames.len() < MAX_REPLAY_FRAMES {
+                    replay.frames.push(frame);
+                }
+                else {
+                    warn!("The game ran longer than a replay can hold, it will not be saved.");
+                    replay_recorder.replay = None;
+                }
+            }
+            // Only the first step of a frame gets the launch.
+            world.resource_mut::<PlayerInput>().launch = false;
+
+            if waiting {
+                break;
+            }
+        }
+
+        game_step_clock.lag.as_secs_f32() / GAME_STEP_DURATION.as_secs_f32()
+    })
+}
+
+/// Returns how far the frame time got towards the next recorded step.
+fn run_replay_steps(world: &mut World) -> f32 {
+    let frame_delta = if is_simulating(world) { world.resource::<Time<Virtual>>().delta() } else { Duration::ZERO };
+    let steps = world.resource_mut::<ReplayPlayback>().start_frame(frame_delta);
+
+    for _ in 0..steps {
+        if !is_simulating(world) {
+            break;
+        }
+        let Some((frame, time)) = world.resource_mut::<ReplayPlayback>().advance() else {
+            break;
+        };
+
+        *world.resource_mut::<PlayerInput>() = frame.input;
+        if run_game_step(world, time) {
+            break;
+        }
+    }
+
+    world.resource::<ReplayPlayback>().get_interpolation_alpha()
+}
+
+pub fn reset_game_step_clock(
+    mut commands: Commands,
+)
+{
+    commands.insert_resource(GameStepClock::default());
+}
+
+pub fn start_recording(
+    replay_playback: Option<Res<ReplayPlayback>>,
+    game_mode: Res<GameMode>,
+    game_rng: Res<GameRng>,
+    current_level: Res<CurrentLevel>,
+    mut replay_recorder: ResMut<ReplayRecorder>,
+)
+{
+    replay_recorder.replay = if replay_playback.is_some() {
+        None
+    }
+    else {
+        Some(Replay {
+            seed: game_rng.get_seed(),
+            level_path: current_level.path.clone(),
+            mode: *game_mode,
+            frames: Vec::new(),
+        })
+    };
+}
+
+pub fn finish_recording(
+    mut commands: Commands,
+    mut replay_recorder: ResMut<ReplayRecorder>,
+)
+{
+    if let Some(replay) = replay_recorder.finish() {
+        save_last_replay(&replay);
+        commands.insert_resource(LastReplay(replay));
+    }
+}
+
+pub fn rewind_replay_playback(
+    replay_playback: Option<ResMut<ReplayPlayback>>,
+)
+{
+    if let Some(mut replay_playback) = replay_playback {
+        replay_playback.rewind();
+    }
+}
+
+pub fn check_replay_rewind(
+    replay_playback: Res<ReplayPlayback>,
+    mut next_state: ResMut<NextState<AppState>>,
+)
+{
+    if replay_playback.needs_rewind() {
+        next_state.set(AppState::RestartInGame);
+    }
+}
+
+/// Watches the latest live session, its level is picked up again when the game restarts.
+pub fn check_replay_requested(
+    mut commands: Commands,
+    mut replay_requested_events: EventReader<ReplayRequested>,
+    last_replay: Option<Res<LastReplay>>,
+    mut next_state: ResMut<NextState<AppState>>,
+)
+{
+    if replay_requested_events.is_empty() {
+        return;
+    }
+    replay_requested_events.clear();
+
+    let Some(last_replay) = last_replay else {
+        return;
+    };
+
+    commands.insert_resource(ReplayPlayback::new(last_replay.0.clone()));
+    next_state.set(AppState::RestartInGame);
+}
+
+pub fn stop_replay(
+    mut commands: Commands,
+)
+{
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_replay(mode: GameMode) -> Replay {
+        let input = PlayerInput {
+            move_left: true,
+            ..default()
+        };
+        let frames = [
+            PlayerInput::default(),
+            PlayerInput::default(),
+            input,
+            PlayerInput { launch: true, ..input },
+            PlayerInput { stick_x: -0.5, ..default() },
+            PlayerInput { target_x: Some(320.), ..default() },
+        ]
+            .into_iter()
+            .map(|input| ReplayFrame { delta: GAME_STEP_DURATION, input })
+            .collect();
+
+        Replay {
+            seed: 1234567890123,
+            level_path: "levels/campaign/stage_2.level.ron".to_string(),
+            mode,
+            frames,
+        }
+    }
+
+    fn write_to_bytes(replay: &Replay) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        replay.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trips_every_game_mode() {
+        let modes = [
+            GameMode::Endless,
+            GameMode::TimeAttack,
+            GameMode::AdvancingWall,
+            GameMode::Campaign { stage: 0, carry_over: None },
+            GameMode::Campaign { stage: 2, carry_over: Some(CarryOver { score: 440000, lives: 2 }) },
+        ];
+
+        for mode in modes {
+            let replay = get_test_replay(mode);
+            let loaded = Replay::read(&mut write_to_bytes(&replay).as_slice()).unwrap();
+
+            assert_eq!(loaded.seed, replay.seed);
+            assert_eq!(loaded.level_path, replay.level_path);
+            assert!(loaded.mode == replay.mode);
+            assert!(loaded.frames == replay.frames);
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let result = Replay::read(&mut b"RIFF\x05".as_slice());
+        assert!(matches!(result, Err(ReplayError::NotAReplay)));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut bytes = write_to_bytes(&get_test_replay(GameMode::Endless));
+        bytes[REPLAY_MAGIC.len()] = REPLAY_VERSION + 1;
+        let result = Replay::read(&mut bytes.as_slice());
+        assert!(matches!(result, Err(ReplayError::UnsupportedVersion(version)) if version == REPLAY_VERSION + 1));
+    }
+
+    #[test]
+    fn rejects_unknown_game_modes() {
+        let replay = get_test_replay(GameMode::Endless);
+        let mut bytes = write_to_bytes(&replay);
+        // The mode tag follows the magic, the version, the seed in six bytes and the level path behind its one byte length.
+        let mode_index = REPLAY_MAGIC.len() + 1 + 6 + 1 + replay.level_path.len();
+        assert_eq!(bytes[mode_index], 0);
+        bytes[mode_index] = 0x7f;
+        let result = Replay::read(&mut bytes.as_slice());
+        assert!(matches!(result, Err(ReplayError::Corrupted)));
+    }
+
+    #[test]
+    fn rejects_unknown_campaign_stages() {
+        let replay = get_test_replay(GameMode::Campaign { stage: CAMPAIGN_STAGE_PATHS.len(), carry_over: None });
+        let result = Replay::read(&mut write_to_bytes(&replay).as_slice());
+        assert!(matches!(result, Err(ReplayError::Corrupted)));
+    }
+
+    #[test]
+    fn rejects_overlong_level_paths() {
+        let mut bytes = REPLAY_MAGIC.to_vec();
+        bytes.push(REPLAY_VERSION);
+        write_varint(&mut bytes, 7).unwrap();
+        write_varint(&mut bytes, u64::MAX).unwrap();
+        let result = Replay::read(&mut bytes.as_slice());
+        assert!(matches!(result, Err(ReplayError::Corrupted)));
+    }
+
+    #[test]
+    fn rejects_overlong_replays() {
+        let replay = Replay {
+            frames: Vec::new(),
+            ..get_test_replay(GameMode::Endless)
+        };
+        let mut bytes = write_to_bytes(&replay);
+        // Replace the empty list of runs by two runs that only fit on their own.
+        bytes.pop();
+        write_varint(&mut bytes, 2).unwrap();
+        for run_length in [1, MAX_REPLAY_FRAMES] {
+            write_varint(&mut bytes, run_length as u64).unwrap();
+            write_varint(&mut bytes, GAME_STEP_DURATION.as_nanos() as u64).unwrap();
+            bytes.push(0);
+        }
+        let result = Replay::read(&mut bytes.as_slice());
+        assert!(matches!(result, Err(ReplayError::Corrupted)));
+    }
+
+    #[test]
+    fn fails_on_truncated_files() {
+        let bytes = write_to_bytes(&get_test_replay(GameMode::TimeAttack));
+        for length in [3, 8, bytes.len() - 1] {
+            assert!(Replay::read(&mut &bytes[..length]).is_err());
+        }
+    }
+}
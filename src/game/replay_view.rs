@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use crate::common::better_button::ReleaseButton;
+use crate::common::shortcuts::{PLAYBACK_PAUSE_KEY, PLAYBACK_SPEED_KEY, SEEK_BACKWARD_KEY, SEEK_FORWARD_KEY};
+use crate::common::styles::spawn_hud_button;
+use crate::game::replay::{PLAYBACK_SPEEDS, ReplayPlayback};
+
+/// How far the seek buttons jump, in recorded steps.
+const SEEK_FRAMES: usize = 1200;
+
+#[derive(Component)]
+pub struct ReplayView;
+#[derive(Component)]
+pub struct ReplayStatusText;
+#[derive(Component)]
+pub struct SeekBar;
+#[derive(Component)]
+pub struct SeekBarProgress;
+#[derive(Component, Default)]
+pub struct PauseButton;
+#[derive(Component, Default)]
+pub struct SpeedButton;
+#[derive(Component, Default)]
+pub struct SeekBackwardButton;
+#[derive(Component, Default)]
+pub struct SeekForwardButton;
+
+pub fn spawn_replay_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+)
+{
+    if replay_playback.is_none() {
+        return;
+    }
+
+    commands.spawn(
+        (
+            ReplayView {},
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Auto,
+                    height: Val::Px(32.),
+                    top: Val::Px(12.),
+                    right: Val::Px(12.),
+                    justify_content: JustifyContent::SpaceAround,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.),
+                    ..default()
+                },
+                ..default()
+            }
+        )
+    ).with_children(|parent| {
+        parent.spawn(
+            (
+                ReplayStatusText {},
+                TextBundle {
+                    text: Text::from_section("", TextStyle {
+                        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                        font_size: 24.,
+                        color: Color::BLACK,
+                    }),
+                    ..default()
+                }
+            )
+        );
+        parent.spawn(
+            (
+                SeekBar {},
+                RelativeCursorPosition::default(),
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(200.),
+                        height: Val::Px(12.),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.6, 0.6, 0.6).into(),
+                    ..default()
+                }
+            )
+        ).with_children(|parent| {
+            parent.spawn(
+                (
+                    SeekBarProgress {},
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(0.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        background_color: Color::BLACK.into(),
+                        ..default()
+                    }
+                )
+            );
+        });
+        spawn_hud_button::<SeekBackwardButton>(parent, &asset_server, "<<", SEEK_BACKWARD_KEY);
+        spawn_hud_button::<PauseButton>(parent, &asset_server, "Pause", PLAYBACK_PAUSE_KEY);
+        spawn_hud_button::<SpeedButton>(parent, &asset_server, "Speed", PLAYBACK_SPEED_KEY);
+        spawn_hud_button::<SeekForwardButton>(parent, &asset_server, ">>", SEEK_FORWARD_KEY);
+    });
+}
+
+pub fn despawn_replay_view(
+    mut commands: Commands,
+    view_query: Query<Entity, With<ReplayView>>,
+)
+{
+    for view in view_query.iter() {
+        commands.entity(view).despawn_recursive();
+    }
+}
+
+pub fn update_replay_view(
+    replay_playback: Res<ReplayPlayback>,
+    mut status_query: Query<&mut Text, With<ReplayStatusText>>,
+    mut progress_query: Query<&mut Style, With<SeekBarProgress>>,
+)
+{
+    let progress = replay_playback.get_frame() as f32 / replay_playback.get_number_of_frames().max(1) as f32;
+    for mut style in progress_query.iter_mut() {
+        style.width = Val::Percent(100. * progress);
+    }
+
+    let status = if replay_playback.is_seeking() {
+        "seeking".to_string()
+    }
+    else if replay_playback.is_finished() {
+        "finished".to_string()
+    }
+    else if replay_playback.is_paused() {
+        "paused".to_string()
+    }
+    else {
+        format!("{}x", replay_playback.get_speed())
+    };
+
+    for mut text in status_query.iter_mut() {
+        text.sections[0].value = format!(
+            "Replay {}/{} {}",
+            replay_playback.get_frame(),
+            replay_playback.get_number_of_frames(),
+            status,
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn check_replay_view_interactions(
+    seek_bar_query: Query<(&Interaction, &RelativeCursorPosition), (Changed<Interaction>, With<SeekBar>)>,
+    pause_button_query: Query<&ReleaseButton, With<PauseButton>>,
+    speed_button_query: Query<&ReleaseButton, With<SpeedButton>>,
+    seek_backward_button_query: Query<&ReleaseButton, With<SeekBackwardButton>>,
+    seek_forward_button_query: Query<&ReleaseButton, With<SeekForwardButton>>,
+    mut replay_playback: ResMut<ReplayPlayback>,
+)
+{
+    for (interaction, cursor_position) in seek_bar_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(normalized) = cursor_position.normalized {
+            let frame = normalized.x.clamp(0., 1.) * replay_playback.get_number_of_frames() as f32;
+            replay_playback.seek(frame.round() as usize);
+        }
+    }
+
+    for button in pause_button_query.iter() {
+        if button.just_released {
+            replay_playback.toggle_pause();
+        }
+    }
+
+    for button in speed_button_query.iter() {
+        if button.just_released {
+            let speed_index = PLAYBACK_SPEEDS
+                .iter()
+                .position(|speed| *speed == replay_playback.get_speed())
+                .unwrap_or(0);
+            replay_playback.set_speed(PLAYBACK_SPEEDS[(speed_index + 1) % PLAYBACK_SPEEDS.len()]);
+        }
+    }
+
+    for button in seek_backward_button_query.iter() {
+        if button.just_released {
+            let frame = replay_playback.get_frame().saturating_sub(SEEK_FRAMES);
+            replay_playback.seek(frame);
+        }
+    }
+
+    for button in seek_forward_button_query.iter() {
+        if button.just_released {
+            let frame = replay_playback.get_frame() + SEEK_FRAMES;
+            replay_playback.seek(frame);
+        }
+    }
+}
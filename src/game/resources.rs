@@ -1,11 +1,28 @@
 use std::time::Duration;
 use bevy::prelude::*;
-use crate::game::ball::{BALL_SIZE, BallObstacleType};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+use crate::game::ball::BALL_SIZE;
 use crate::game::paddle::PADDLE_WIDTH;
 
 #[derive(Resource, Default)]
 pub struct Score(pub usize);
 
+/// How often the player may still lose the last ball in play, the game is over once none are left.
+#[derive(Resource)]
+pub struct Lives(pub usize);
+
+impl Lives {
+    pub const DEFAULT: usize = 3;
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(Self::DEFAULT)
+    }
+}
+
 #[derive(Resource)]
 pub struct BrickRowSpawnCooldown(pub Timer);
 
@@ -15,24 +32,48 @@ impl Default for BrickRowSpawnCooldown {
     }
 }
 
+/// Until the advancing wall pushes in its next row.
 #[derive(Resource)]
+pub struct WallAdvanceTimer(pub Timer);
+
+impl Default for WallAdvanceTimer {
+    fn default() -> Self {
+        Self(Timer::new(Duration::from_secs(10), TimerMode::Once))
+    }
+}
+
+/// The speed new balls start with, each ball keeps its own copy.
+#[derive(Resource, Clone, Copy)]
 pub struct BallSpeed {
     points: usize,
 }
 
 impl BallSpeed {
-    const DEFAULT_POINTS: usize = 3;
+    pub const DEFAULT_POINTS: usize = 3;
     const POINT_SPEEDS: &'static [f32] = &[250., 325., 400., 500.0, 600., 700., 900., 1100., 1300.];
 
-    pub fn change_points(&mut self, delta_points: i32) {
+    /// Returns how many points actually changed, the limits can swallow some.
+    pub fn change_points(&mut self, delta_points: i32) -> i32 {
+        let previous_points = self.points;
         let points = (self.points as i32 + delta_points).max(0);
         self.points = (points as usize).min(Self::POINT_SPEEDS.len() - 1);
-        println!("BallSpeed.points: {}/{}", self.points, Self::POINT_SPEEDS.len() - 1);
+        debug!("BallSpeed.points: {}/{}", self.points, Self::POINT_SPEEDS.len() - 1);
+        self.points as i32 - previous_points as i32
+    }
+
+    pub fn set_points(&mut self, points: usize) {
+        self.points = points.min(Self::POINT_SPEEDS.len() - 1);
     }
 
     pub fn get_speed(&self) -> f32 {
         Self::POINT_SPEEDS[self.points]
     }
+
+    /// How far above the default speed the ball is, from 0 at or below it to 1 at the top speed.
+    pub fn get_boost(&self) -> f32 {
+        let max_points = Self::POINT_SPEEDS.len() - 1;
+        self.points.saturating_sub(Self::DEFAULT_POINTS) as f32 / (max_points - Self::DEFAULT_POINTS) as f32
+    }
 }
 
 impl Default for BallSpeed {
@@ -43,19 +84,27 @@ impl Default for BallSpeed {
     }
 }
 
-#[derive(Resource)]
+/// The size new balls start with, each ball keeps its own copy.
+#[derive(Resource, Clone, Copy)]
 pub struct BallSize {
     points: usize,
 }
 
 impl BallSize {
-    const DEFAULT_POINTS: usize = 0;
+    pub const DEFAULT_POINTS: usize = 0;
     const POINT_SCALES: &'static [f32] = &[1.0, 2.0, 3.0];
 
-    pub fn change_points(&mut self, delta_points: i32) {
+    /// Returns how many points actually changed, the limits can swallow some.
+    pub fn change_points(&mut self, delta_points: i32) -> i32 {
+        let previous_points = self.points;
         let points = (self.points as i32 + delta_points).max(0);
         self.points = (points as usize).min(Self::POINT_SCALES.len() - 1);
-        println!("BallSize.points: {}/{}", self.points, Self::POINT_SCALES.len() - 1);
+        debug!("BallSize.points: {}/{}", self.points, Self::POINT_SCALES.len() - 1);
+        self.points as i32 - previous_points as i32
+    }
+
+    pub fn set_points(&mut self, points: usize) {
+        self.points = points.min(Self::POINT_SCALES.len() - 1);
     }
 
     pub fn get_scale(&self) -> f32 {
@@ -86,13 +135,20 @@ pub struct PaddleSpeed {
 }
 
 impl PaddleSpeed {
-    const DEFAULT_POINTS: usize = 4;
+    pub const DEFAULT_POINTS: usize = 4;
     const POINT_SPEEDS: &'static [f32] = &[200., 250., 300., 350., 400., 500., 600., 800., 1000., 1200., 1400.];
 
-    pub fn change_points(&mut self, delta_points: i32) {
+    /// Returns how many points actually changed, the limits can swallow some.
+    pub fn change_points(&mut self, delta_points: i32) -> i32 {
+        let previous_points = self.points;
         let points = (self.points as i32 + delta_points).max(0);
         self.points = (points as usize).min(Self::POINT_SPEEDS.len() - 1);
-        println!("PaddleSpeed.points: {}/{}", self.points, Self::POINT_SPEEDS.len() - 1);
+        debug!("PaddleSpeed.points: {}/{}", self.points, Self::POINT_SPEEDS.len() - 1);
+        self.points as i32 - previous_points as i32
+    }
+
+    pub fn set_points(&mut self, points: usize) {
+        self.points = points.min(Self::POINT_SPEEDS.len() - 1);
     }
 
     pub fn get_speed(&self) -> f32 {
@@ -114,14 +170,21 @@ pub struct PaddleSize {
 }
 
 impl PaddleSize {
-    const DEFAULT_POINTS: usize = 3;
+    pub const DEFAULT_POINTS: usize = 3;
     const MIN_WIDTH: f32 = 0.75 * PADDLE_WIDTH;
     const POINT_EXTRA_WIDTHS: &'static [f32] = &[0., 25., 49., 81., 121., 169., 225., 289., 361., 441., 529.];
 
-    pub fn change_points(&mut self, delta_points: i32) {
+    /// Returns how many points actually changed, the limits can swallow some.
+    pub fn change_points(&mut self, delta_points: i32) -> i32 {
+        let previous_points = self.points;
         let points = (self.points as i32 + delta_points).max(0);
         self.points = (points as usize).min(Self::POINT_EXTRA_WIDTHS.len() - 1);
-        println!("PaddleSize.points: {}/{}", self.points, Self::POINT_EXTRA_WIDTHS.len() - 1);
+        debug!("PaddleSize.points: {}/{}", self.points, Self::POINT_EXTRA_WIDTHS.len() - 1);
+        self.points as i32 - previous_points as i32
+    }
+
+    pub fn set_points(&mut self, points: usize) {
+        self.points = points.min(Self::POINT_EXTRA_WIDTHS.len() - 1);
     }
 
     pub fn get_width(&self) -> f32 {
@@ -137,6 +200,7 @@ impl Default for PaddleSize {
     }
 }
 
+/// Whether new balls start as ghosts, each ball keeps its own flag.
 #[derive(Resource, Default)]
 pub struct BrickGhost {
     enabled: bool,
@@ -145,15 +209,97 @@ pub struct BrickGhost {
 impl BrickGhost {
     pub fn set_enabled(&mut self, value: bool) {
         self.enabled = value;
-        println!("BrickGhost.enabled: {}", value);
+        debug!("BrickGhost.enabled: {}", value);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// How the paddle sends the ball back up.
+#[derive(Resource, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct PaddleDeflection {
+    /// Angle from straight up, in degrees, for a ball hitting the very end of the paddle. The center sends it straight up.
+    pub max_angle: f32,
+    /// The flattest a bounce may get, in degrees above horizontal, so the ball never crawls along the bottom.
+    pub min_vertical_angle: f32,
+    /// How much of the paddle velocity, relative to the ball speed, is added to the bounce. Zero turns spin off.
+    pub spin: f32,
+}
+
+impl PaddleDeflection {
+    /// The direction of a ball leaving the paddle.
+    ///
+    /// `hit_offset` goes from -1 at the left end of the paddle to 1 at the right end.
+    pub fn get_direction(&self, hit_offset: f32, paddle_velocity_x: f32, ball_speed: f32) -> Vec2 {
+        let angle = hit_offset.clamp(-1., 1.) * self.max_angle.to_radians();
+        let mut direction = Vec2::new(angle.sin(), angle.cos());
+        if ball_speed > 0. {
+            direction.x += self.spin * paddle_velocity_x / ball_speed;
+        }
+        let direction = direction.normalize_or_zero();
+
+        let min_y = self.min_vertical_angle.to_radians().sin();
+        if direction.y >= min_y {
+            return direction;
+        }
+        let max_x = (1. - min_y * min_y).sqrt();
+        Vec2::new(max_x.copysign(direction.x), min_y)
     }
+}
 
-    pub fn get_obstacle_type(&self) -> BallObstacleType {
-        if self.enabled {
-            BallObstacleType::Ghost
+impl Default for PaddleDeflection {
+    fn default() -> Self {
+        Self {
+            max_angle: 60.,
+            min_vertical_angle: 20.,
+            spin: 0.25,
         }
-        else {
-            BallObstacleType::Natural
+    }
+}
+
+/// The only source of randomness in the game, so a run can be reproduced from its seed.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
-}
\ No newline at end of file
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use crate::game::events::PointsScored;
+
+const POPUP_LIFETIME_SECONDS: f32 = 0.8;
+const POPUP_RISE_SPEED: f32 = 60.;
+const POPUP_FONT_SIZE: f32 = 24.;
+const POPUP_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+
+/// The points a destroyed brick scored, floating up from where the brick was and fading out.
+#[derive(Component)]
+pub struct ScorePopup {
+    /// Seconds since the popup showed up.
+    age: f32,
+}
+
+pub fn spawn_score_popups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut points_scored_events: EventReader<PointsScored>,
+)
+{
+    for event in points_scored_events.read() {
+        commands.spawn(
+            (
+                ScorePopup { age: 0. },
+                Text2dBundle {
+                    text: Text::from_section(format!("+{}", event.points), TextStyle {
+                        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                        font_size: POPUP_FONT_SIZE,
+                        color: POPUP_COLOR,
+                    }),
+                    transform: Transform::from_xyz(event.position.x, event.position.y, 5.),
+                    ..default()
+                },
+            )
+        );
+    }
+}
+
+pub fn update_score_popups(
+    mut commands: Commands,
+    mut popup_query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+    time: Res<Time>,
+)
+{
+    for (entity, mut popup, mut transform, mut text) in popup_query.iter_mut() {
+        popup.age += time.delta_seconds();
+        if popup.age >= POPUP_LIFETIME_SECONDS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        text.sections[0].style.color.set_a(1. - popup.age / POPUP_LIFETIME_SECONDS);
+    }
+}
+
+pub fn despawn_score_popups(
+    mut commands: Commands,
+    popup_query: Query<Entity, With<ScorePopup>>,
+)
+{
+    for popup in popup_query.iter() {
+        commands.entity(popup).despawn();
+    }
+}
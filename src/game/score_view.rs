@@ -1,14 +1,44 @@
+use std::f32::consts::TAU;
 use bevy::prelude::*;
-use crate::game::resources::Score;
+use crate::game::collectable::{CollectableSet, CurrentCollectableSet};
+use crate::game::combo::Combo;
+use crate::game::effects::ActiveEffects;
+use crate::game::game_mode::{GameMode, RemainingTime};
+use crate::game::resources::{Lives, Score};
+
+const LIVES_TEXTURE_PATH: &str = "sprites/collectables/extra_life.png";
+const EFFECT_SLOT_SIZE: f32 = 32.;
+const EFFECT_ICON_SIZE: f32 = 20.;
+const EFFECT_RING_SEGMENTS: usize = 12;
+const EFFECT_RING_SEGMENT_SIZE: f32 = 4.;
+const EFFECT_RING_LIT_COLOR: Color = Color::BLACK;
+const EFFECT_RING_UNLIT_COLOR: Color = Color::rgba(0., 0., 0., 0.15);
 
 #[derive(Component)]
 pub struct ScoreView;
 #[derive(Component)]
 pub struct ScoreText;
+#[derive(Component)]
+pub struct LivesText;
+/// Counts down the time left in a time attack, hidden in the other modes.
+#[derive(Component)]
+pub struct RemainingTimeText;
+/// Shows the combo multiplier, empty while there is none.
+#[derive(Component)]
+pub struct ComboText;
+/// Shows how long the timed effects of a collectable keep running, only there while they run.
+#[derive(Component)]
+pub struct EffectSlot(usize);
+/// One dot of the countdown ring around an effect icon, numbered clockwise from the top.
+#[derive(Component)]
+pub struct EffectRingSegment(usize);
+#[derive(Component)]
+pub struct EffectStacksText;
 
 pub fn spawn_score_view(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    game_mode: Res<GameMode>,
 )
 {
     commands.spawn(
@@ -55,7 +85,139 @@ pub fn spawn_score_view(
                         font: asset_server.load("fonts/OpenSans-Regular.ttf"),
                         font_size: 30.,
                         color: Color::BLACK,
+                    }),
+                    ..default()
+                }
+            )
+        );
+        parent.spawn(
+            ImageBundle {
+                style: Style {
+                    height: Val::Percent(75.),
+                    ..default()
+                },
+                image: UiImage {
+                    texture: asset_server.load(LIVES_TEXTURE_PATH),
+                    ..default()
+                },
+                ..default()
+            }
+        );
+        parent.spawn(
+            (
+                LivesText {},
+                TextBundle {
+                    text: Text::from_section("x 0", TextStyle {
+                        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                        font_size: 30.,
+                        color: Color::BLACK,
+                    }),
+                    ..default()
+                }
+            )
+        );
+        parent.spawn(
+            (
+                RemainingTimeText {},
+                TextBundle {
+                    style: Style {
+                        display: if *game_mode == GameMode::TimeAttack { Display::Flex } else { Display::None },
+                        ..default()
+                    },
+                    text: Text::from_section("", TextStyle {
+                        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                        font_size: 30.,
+                        color: Color::BLACK,
+                    }),
+                    ..default()
+                }
+            )
+        );
+        parent.spawn(
+            (
+                ComboText {},
+                TextBundle {
+                    text: Text::from_section("", TextStyle {
+                        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                        font_size: 30.,
+                        color: Color::BLACK,
+                    }),
+                    ..default()
+                }
+            )
+        );
+    });
+}
+
+fn spawn_effect_slot(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>, collectable: usize, texture_path: &str) {
+    parent.spawn(
+        (
+            EffectSlot(collectable),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(EFFECT_SLOT_SIZE),
+                    height: Val::Px(EFFECT_SLOT_SIZE),
+                    ..default()
+                },
+                ..default()
+            }
+        )
+    ).with_children(|parent| {
+        let icon_offset = (EFFECT_SLOT_SIZE - EFFECT_ICON_SIZE) / 2.;
+        parent.spawn(
+            ImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(icon_offset),
+                    top: Val::Px(icon_offset),
+                    width: Val::Px(EFFECT_ICON_SIZE),
+                    height: Val::Px(EFFECT_ICON_SIZE),
+                    ..default()
+                },
+                image: UiImage {
+                    texture: asset_server.load(texture_path.to_string()),
+                    ..default()
+                },
+                ..default()
+            }
+        );
+
+        let radius = (EFFECT_SLOT_SIZE - EFFECT_RING_SEGMENT_SIZE) / 2.;
+        for index in 0..EFFECT_RING_SEGMENTS {
+            let angle = TAU * index as f32 / EFFECT_RING_SEGMENTS as f32;
+            parent.spawn(
+                (
+                    EffectRingSegment(index),
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(radius + radius * angle.sin()),
+                            top: Val::Px(radius - radius * angle.cos()),
+                            width: Val::Px(EFFECT_RING_SEGMENT_SIZE),
+                            height: Val::Px(EFFECT_RING_SEGMENT_SIZE),
+                            ..default()
+                        },
+                        background_color: EFFECT_RING_LIT_COLOR.into(),
                         ..default()
+                    }
+                )
+            );
+        }
+
+        parent.spawn(
+            (
+                EffectStacksText {},
+                TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(-4.),
+                        bottom: Val::Px(-6.),
+                        ..default()
+                    },
+                    text: Text::from_section("", TextStyle {
+                        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                        font_size: 14.,
+                        color: Color::BLACK,
                     }),
                     ..default()
                 }
@@ -86,4 +248,96 @@ pub fn update_score_view(
     for mut indicator in indicator_query.iter_mut() {
         indicator.sections[0].value = format!("x {}", score.0);
     }
-}
\ No newline at end of file
+}
+
+pub fn update_lives_view(
+    lives: Res<Lives>,
+    mut indicator_query: Query<&mut Text, With<LivesText>>,
+)
+{
+    if !lives.is_changed() {
+        return;
+    }
+
+    for mut indicator in indicator_query.iter_mut() {
+        indicator.sections[0].value = format!("x {}", lives.0);
+    }
+}
+
+pub fn update_combo_view(
+    combo: Res<Combo>,
+    mut indicator_query: Query<&mut Text, With<ComboText>>,
+)
+{
+    if !combo.is_changed() {
+        return;
+    }
+
+    let multiplier = combo.get_multiplier();
+    for mut indicator in indicator_query.iter_mut() {
+        indicator.sections[0].value = if multiplier > 1 { format!("Combo x{}", multiplier) } else { String::new() };
+    }
+}
+
+pub fn update_remaining_time_view(
+    remaining_time: Res<RemainingTime>,
+    mut indicator_query: Query<&mut Text, With<RemainingTimeText>>,
+)
+{
+    if !remaining_time.is_changed() {
+        return;
+    }
+
+    let seconds = remaining_time.0.ceil() as usize;
+    for mut indicator in indicator_query.iter_mut() {
+        indicator.sections[0].value = format!("{}:{:02}", seconds / 60, seconds % 60);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_active_effects_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    active_effects: Res<ActiveEffects>,
+    collectable_sets: Res<Assets<CollectableSet>>,
+    current_collectable_set: Res<CurrentCollectableSet>,
+    view_query: Query<Entity, With<ScoreView>>,
+    slot_query: Query<(Entity, &EffectSlot, &Children)>,
+    mut segment_query: Query<(&EffectRingSegment, &mut BackgroundColor)>,
+    mut stacks_query: Query<&mut Text, With<EffectStacksText>>,
+)
+{
+    if !active_effects.is_changed() {
+        return;
+    }
+
+    for (entity, slot, children) in slot_query.iter() {
+        let Some((progress, stacks)) = active_effects.get_progress(slot.0) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let lit_segments = (progress * EFFECT_RING_SEGMENTS as f32).ceil() as usize;
+        for &child in children.iter() {
+            if let Ok((segment, mut background_color)) = segment_query.get_mut(child) {
+                *background_color = if segment.0 < lit_segments { EFFECT_RING_LIT_COLOR } else { EFFECT_RING_UNLIT_COLOR }.into();
+            }
+            if let Ok(mut text) = stacks_query.get_mut(child) {
+                text.sections[0].value = if stacks > 1 { format!("x{stacks}") } else { String::new() };
+            }
+        }
+    }
+
+    // New effects get a slot at the end, it starts out with a full ring.
+    let (Ok(view), Some(collectable_set)) = (view_query.get_single(), current_collectable_set.get(&collectable_sets)) else {
+        return;
+    };
+    for collectable in active_effects.get_collectables() {
+        if slot_query.iter().all(|(_, slot, _)| slot.0 != collectable) {
+            let texture_path = &collectable_set.collectables[collectable].texture;
+            commands.entity(view).with_children(|parent| {
+                spawn_effect_slot(parent, &asset_server, collectable, texture_path);
+            });
+        }
+    }
+}
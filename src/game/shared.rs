@@ -1,92 +1,138 @@
 use bevy::prelude::*;
-use crate::game::collectable::{Collectable, CollectableType};
-use super::resources::{BallSize, BallSpeed, BrickGhost, PaddleSize, PaddleSpeed, Score};
+use crate::game::textures::Textures;
+use crate::game::collectable::{Collectable, CollectableSet, CurrentCollectableSet};
+use crate::game::events::CollectableCollected;
+use super::resources::{Lives, Score};
+use super::power_ups::{Shield, spawn_shield};
+use super::effects::{ActiveEffects, CollectableEffect, EffectSubjects, EffectTarget, TimedEffect};
 use super::ball::{Ball, clone_balls, declone_balls};
+use super::broad_phase::CollisionGrid;
 use super::collider::BoxCollider;
 use super::paddle::Paddle;
 
 pub fn keep_ball_at_paddle_center (
     paddle_query: Query<(&Transform, &BoxCollider), With<Paddle>>,
-    mut ball_query: Query<&mut Transform, (With<Ball>, Without<Paddle>)>,
-    ball_size: Res<BallSize>,
+    mut ball_query: Query<(&mut Transform, &Ball), Without<Paddle>>,
 )
 {
     if let Ok((paddle_transform, paddle_collider)) = paddle_query.get_single() {
-        for mut ball in ball_query.iter_mut() {
-            ball.translation = Vec3 {
+        for (mut ball_transform, ball) in ball_query.iter_mut() {
+            ball_transform.translation = Vec3 {
                 x: paddle_transform.translation.x,
-                y: paddle_transform.translation.y + paddle_collider.extends.y + ball_size.get_radius(),
+                y: paddle_transform.translation.y + paddle_collider.extends.y + ball.size.get_radius(),
                 z: 0.,
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn collect_collectables(
     mut commands: Commands,
-    collectable_query: Query<(Entity, &Transform, &BoxCollider, &Collectable)>,
+    collectable_query: Query<&Collectable>,
+    collectable_sets: Res<Assets<CollectableSet>>,
+    current_collectable_set: Res<CurrentCollectableSet>,
     paddle_query: Query<(&Transform, &BoxCollider), With<Paddle>>,
-    ball_query: Query<(&Ball, &Transform)>,
+    collision_grid: Res<CollisionGrid>,
+    ball_query: Query<(Entity, &Transform), With<Ball>>,
     ball_entity_query: Query<Entity, With<Ball>>,
+    shield_query: Query<(), With<Shield>>,
     mut score: ResMut<Score>,
-    mut ball_size: ResMut<BallSize>,
-    mut ball_speed: ResMut<BallSpeed>,
-    mut paddle_size: ResMut<PaddleSize>,
-    mut paddle_speed: ResMut<PaddleSpeed>,
-    mut brick_ghost: ResMut<BrickGhost>,
-    asset_server: Res<AssetServer>,
+    mut lives: ResMut<Lives>,
+    mut active_effects: ResMut<ActiveEffects>,
+    mut effect_subjects: EffectSubjects,
+    textures: Textures,
+    mut collectable_collected_events: EventWriter<CollectableCollected>,
 )
 {
+    let Some(collectable_set) = current_collectable_set.get(&collectable_sets) else {
+        return;
+    };
+
     if let Ok((paddle_transform, paddle_collider)) = paddle_query.get_single() {
-        for (entity, transform, collider, collectable) in collectable_query.iter() {
+        let paddle_position = paddle_transform.translation.xy();
+        let mut candidates = Vec::new();
+        let mut has_shield = !shield_query.is_empty();
+        collision_grid.find_candidates(paddle_position - paddle_collider.extends, paddle_position + paddle_collider.extends, &mut candidates);
+
+        for entry in candidates.into_iter().map(|index| collision_grid.get_entries()[index]) {
+            let Ok(collectable) = collectable_query.get(entry.entity) else {
+                continue;
+            };
+            let entity = entry.entity;
             let overlap = BoxCollider::overlap(
-                paddle_transform.translation.xy(), paddle_collider.extends,
-                transform.translation.xy(), collider.extends,
+                paddle_position, paddle_collider.extends,
+                entry.center, entry.extents,
             );
 
             if overlap {
-                match collectable.collectable_type {
-                    CollectableType::BallClone => {
-                        clone_balls(&mut commands, &asset_server, &ball_query, &ball_size);
-                    }
-                    CollectableType::BallDeclone => {
-                        declone_balls(&mut commands, &ball_entity_query);
-                    }
-                    CollectableType::BallSizeUp => {
-                        ball_size.change_points(1);
-                    }
-                    CollectableType::BallSizeDown => {
-                        ball_size.change_points(-1);
-                    }
-                    CollectableType::BallSpeedUp => {
-                        ball_speed.change_points(1);
-                    }
-                    CollectableType::BallSpeedDown => {
-                        ball_speed.change_points(-1);
-                    }
-                    CollectableType::Coin => {
-                        score.0 += 10000;
-                    }
-                    CollectableType::GhostUp => {
-                        brick_ghost.set_enabled(true);
-                    }
-                    CollectableType::GhostDown => {
-                        brick_ghost.set_enabled(false);
-                    }
-                    CollectableType::PaddleSizeUp => {
-                        paddle_size.change_points(1);
-                    }
-                    CollectableType::PaddleSizeDown => {
-                        paddle_size.change_points(-1);
-                    }
-                    CollectableType::PaddleSpeedUp => {
-                        paddle_speed.change_points(1);
-                    }
-                    CollectableType::PaddleSpeedDown => {
-                        paddle_speed.change_points(-1);
+                for effect in collectable_set.collectables[collectable.definition].effects.iter() {
+                    match *effect {
+                        CollectableEffect::Score(points) => {
+                            score.0 += points;
+                        }
+                        CollectableEffect::Lives(extra_lives) => {
+                            lives.0 += extra_lives;
+                        }
+                        CollectableEffect::CloneBalls => {
+                            let originals: Vec<(Entity, Ball, Vec2)> = ball_query
+                                .iter()
+                                .filter_map(|(ball_entity, transform)| {
+                                    let ball = effect_subjects.get_ball(ball_entity)?;
+                                    Some((ball_entity, ball, transform.translation.xy()))
+                                })
+                                .collect();
+                            for (original, clone) in clone_balls(&mut commands, &textures, &originals) {
+                                active_effects.copy_ball_effects(original, clone);
+                            }
+                        }
+                        CollectableEffect::DecloneBalls => {
+                            declone_balls(&mut commands, &ball_entity_query);
+                        }
+                        CollectableEffect::Shield => {
+                            // Only one shield at a time, another one would break along with it.
+                            if !has_shield {
+                                spawn_shield(&mut commands);
+                                has_shield = true;
+                            }
+                        }
+                        CollectableEffect::Stat { stat, points, duration, max_stacks } => {
+                            // Ball power-ups go to the ball that dropped them, or to every ball once that one is gone.
+                            // Lasers drop them for the ball nearest the paddle.
+                            let targets: Vec<EffectTarget> = if !stat.is_ball_stat() {
+                                vec![EffectTarget::Paddle]
+                            }
+                            else {
+                                let owner = match collectable.ball {
+                                    Some(ball) => ball_query.contains(ball).then_some(ball),
+                                    None => ball_query
+                                        .iter()
+                                        .map(|(ball, transform)| (ball, transform.translation.xy().distance_squared(paddle_position)))
+                                        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                                        .map(|(ball, _)| ball),
+                                };
+                                match owner {
+                                    Some(owner) => vec![EffectTarget::Ball(owner)],
+                                    None => ball_query.iter().map(|(ball, _)| EffectTarget::Ball(ball)).collect(),
+                                }
+                            };
+
+                            for target in targets {
+                                match duration {
+                                    Some(duration) => {
+                                        let timed_effect = TimedEffect { stat, points, duration, max_stacks };
+                                        active_effects.add(collectable.definition, timed_effect, target, &mut effect_subjects);
+                                    }
+                                    None => {
+                                        effect_subjects.change(stat, target, points);
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
 
+                collectable_collected_events.send(CollectableCollected { definition: collectable.definition });
                 commands.entity(entity).despawn();
             }
         }
@@ -1,13 +1,15 @@
 use std::ops::Range;
 use bevy::prelude::*;
-use rand::prelude::random;
+use rand::Rng;
 use crate::game::collider::BoxCollider;
+use crate::game::interpolation::InterpolatedTransform;
 
-#[derive(Bundle, Default)]
+#[derive(Bundle)]
 pub struct SparkBundle {
     pub sprite_bundle: SpriteBundle,
     pub spark: Spark,
     pub box_collider: BoxCollider,
+    pub interpolated_transform: InterpolatedTransform,
 }
 
 #[derive(Component)]
@@ -19,17 +21,6 @@ pub struct Spark {
     speed_to_rotation_speed: f32,
 }
 
-impl Default for Spark {
-    fn default() -> Self {
-        Self::random(
-            200.0..400.0,
-            850.0..950.0,
-            0.3,
-            0.02,
-        )
-    }
-}
-
 impl Spark {
     const TARGET_DIRECTION: Vec2 = Vec2::new(0., -1.);
 
@@ -44,7 +35,19 @@ impl Spark {
             * self.rotation_sign * self.velocity.length() * self.speed_to_rotation_speed);
     }
 
+    /// A spark that pops up a little and then falls down, the way brick drops do.
+    pub fn random_drop(rng: &mut impl Rng) -> Self {
+        Self::random(
+            rng,
+            200.0..400.0,
+            850.0..950.0,
+            0.3,
+            0.02,
+        )
+    }
+
     pub fn random(
+        rng: &mut impl Rng,
         initial_speed: Range<f32>,
         target_speed: Range<f32>,
         velocity_lerp_speed: f32,
@@ -52,15 +55,15 @@ impl Spark {
     ) -> Self
     {
         let initial_speed = initial_speed.start
-            - random::<f32>() * (initial_speed.end - initial_speed.start);
+            - rng.gen::<f32>() * (initial_speed.end - initial_speed.start);
         let initial_velocity = initial_speed
-            * Vec2::new((random::<f32>() - 0.5) * 0.5, random::<f32>() * 0.5).normalize_or_zero();
+            * Vec2::new((rng.gen::<f32>() - 0.5) * 0.5, rng.gen::<f32>() * 0.5).normalize_or_zero();
         let target_speed = target_speed.start
-            - random::<f32>() * (target_speed.end - target_speed.start);
+            - rng.gen::<f32>() * (target_speed.end - target_speed.start);
         Self {
             velocity: initial_velocity,
             target_velocity: target_speed * Self::TARGET_DIRECTION,
-            rotation_sign: if random::<bool>() { 1.0 } else { -1.0 },
+            rotation_sign: if rng.gen::<bool>() { 1.0 } else { -1.0 },
             velocity_lerp_speed,
             speed_to_rotation_speed,
         }
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use crate::common::actions::Action;
+use crate::common::better_button::ReleaseButton;
+use crate::common::shortcuts::MENU_KEY;
+use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header, spawn_full_screen_menu_text};
+use crate::game::campaign::{StageBonus, UnlockedStage, is_last_stage};
+use crate::game::game_mode::GameMode;
+use crate::game::events::{MenuRequested, NextStageRequested};
+use crate::game::resources::{Lives, Score};
+
+#[derive(Component)]
+pub struct StageClearView;
+#[derive(Component, Default)]
+pub struct NextStageButton;
+#[derive(Component, Default)]
+pub struct MenuButton;
+
+pub fn spawn_stage_clear_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_mode: Res<GameMode>,
+    stage_bonus: Res<StageBonus>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+    unlocked_stage: Res<UnlockedStage>,
+)
+{
+    let GameMode::Campaign { stage, .. } = *game_mode else {
+        return;
+    };
+
+    commands.spawn(
+        (
+            StageClearView {},
+            get_full_screen_menu_node_bundle(),
+        )
+    ).with_children(|parent| {
+        let header = if is_last_stage(stage) {
+            "Campaign complete!".to_string()
+        }
+        else {
+            format!("Stage {} clear!", stage + 1)
+        };
+        spawn_full_screen_menu_header(parent, &asset_server, header);
+        spawn_full_screen_menu_text(parent, &asset_server, format!("Clear bonus: {}", stage_bonus.clear));
+        spawn_full_screen_menu_text(parent, &asset_server, format!("Lives bonus ({} left): {}", lives.0, stage_bonus.lives));
+        spawn_full_screen_menu_text(parent, &asset_server, format!("Your score: {}", score.0));
+        if let Some(unlocked_stage) = unlocked_stage.0 {
+            spawn_full_screen_menu_text(parent, &asset_server, format!("Stage {} unlocked!", unlocked_stage + 1));
+        }
+        let next_label = if is_last_stage(stage) { "Finish" } else { "Next" };
+        spawn_full_screen_menu_button::<NextStageButton>(parent, &asset_server, next_label, Action::Confirm);
+        spawn_full_screen_menu_button::<MenuButton>(parent, &asset_server, "Menu", MENU_KEY);
+    });
+}
+
+pub fn despawn_stage_clear_view(
+    mut commands: Commands,
+    view_query: Query<Entity, With<StageClearView>>,
+)
+{
+    for view in view_query.iter() {
+        commands.entity(view).despawn_recursive();
+    }
+}
+
+pub fn check_stage_clear_interactions(
+    next_stage_button_query: Query<&ReleaseButton, With<NextStageButton>>,
+    menu_button_query: Query<&ReleaseButton, With<MenuButton>>,
+    mut next_stage_requested_events: EventWriter<NextStageRequested>,
+    mut menu_requested_events: EventWriter<MenuRequested>,
+)
+{
+    for button in next_stage_button_query.iter() {
+        if button.just_released {
+            next_stage_requested_events.send_default();
+            return;
+        }
+    }
+
+    for button in menu_button_query.iter() {
+        if button.just_released {
+            menu_requested_events.send_default();
+            return;
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use bevy::prelude::*;
+use serde::Serialize;
+use crate::common::storage::export_json_file;
+use crate::game::ball::Ball;
+use crate::game::collectable::{CollectableSet, CurrentCollectableSet};
+use crate::game::events::{BrickDestroyed, CollectableCollected};
+use crate::game::game_mode::GameOverReason;
+use crate::game::paddle::PaddleContact;
+
+const RUN_REPORT_FILE_NAME: &str = "run_statistics.json";
+
+/// What happened during the latest game, counted in game steps so a replay ends up with the same numbers.
+#[derive(Resource, Clone, Default, Serialize)]
+pub struct RunStatistics {
+    pub bricks_destroyed: usize,
+    pub coins_collected: usize,
+    /// How often each collectable other than coins was picked up, by name.
+    pub collectables_collected: BTreeMap<String, usize>,
+    pub max_simultaneous_balls: usize,
+    /// The most seconds of game time the balls stayed in play without touching the paddle.
+    pub longest_rally: f32,
+    /// Seconds of game time in play, pauses and preparations do not count.
+    pub play_time: f32,
+    /// Seconds since the paddle last touched a ball.
+    #[serde(skip)]
+    current_rally: f32,
+}
+
+/// Everything the summary screen tells about a finished game, exported as it is.
+#[derive(Serialize)]
+pub struct RunReport {
+    pub score: usize,
+    pub seed: u64,
+    pub reason: Option<GameOverReason>,
+    pub statistics: RunStatistics,
+}
+
+impl RunReport {
+    /// Writes the report next to the high scores, returns where it went.
+    pub fn export(&self) -> Option<String> {
+        export_json_file(RUN_REPORT_FILE_NAME, self, "run statistics")
+            .map(|path| path.display().to_string())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_run_statistics(
+    mut brick_destroyed_events: EventReader<BrickDestroyed>,
+    mut collectable_collected_events: EventReader<CollectableCollected>,
+    collectable_sets: Res<Assets<CollectableSet>>,
+    current_collectable_set: Res<CurrentCollectableSet>,
+    ball_query: Query<(), With<Ball>>,
+    paddle_contact: PaddleContact,
+    time: Res<Time>,
+    mut run_statistics: ResMut<RunStatistics>,
+)
+{
+    run_statistics.bricks_destroyed += brick_destroyed_events.read().count();
+
+    if let Some(collectable_set) = current_collectable_set.get(&collectable_sets) {
+        for event in collectable_collected_events.read() {
+            let name = &collectable_set.collectables[event.definition].name;
+            if *name == collectable_set.coins.collectable {
+                run_statistics.coins_collected += 1;
+            }
+            else {
+                *run_statistics.collectables_collected.entry(name.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    let number_of_balls = ball_query.iter().count();
+    run_statistics.max_simultaneous_balls = run_statistics.max_simultaneous_balls.max(number_of_balls);
+    run_statistics.play_time += time.delta_seconds();
+
+    // A rally ends when the paddle touches a ball or holds one.
+    if paddle_contact.is_touching() {
+        run_statistics.current_rally = 0.;
+    }
+    else {
+        run_statistics.current_rally += time.delta_seconds();
+        run_statistics.longest_rally = run_statistics.longest_rally.max(run_statistics.current_rally);
+    }
+}
+
+/// A fresh ball waits on the paddle, so a lost ball ends the rally.
+pub fn end_rally(
+    mut run_statistics: ResMut<RunStatistics>,
+)
+{
+    run_statistics.current_rally = 0.;
+}
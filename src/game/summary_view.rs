@@ -1,20 +1,94 @@
 use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+use crate::common::actions::Action;
 use crate::common::better_button::{ReleaseButton};
-use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header};
-use crate::game::events::{MenuRequested, RestartRequested};
-use crate::game::resources::Score;
+use crate::common::shortcuts::{ERASE_NAME_KEY, EXPORT_KEY, MENU_KEY, REPLAY_KEY, RESTART_KEY};
+use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header, spawn_full_screen_menu_text};
+use crate::game::game_mode::{GameMode, GameModeKind, GameOverReason};
+use crate::game::events::{MenuRequested, ReplayRequested, RestartRequested};
+use crate::game::high_scores::{HighScore, HighScores, MAX_PLAYER_NAME_LENGTH};
+use crate::game::replay::{LastReplay, ReplayPlayback};
+use crate::game::resources::{GameRng, Score};
+use crate::game::statistics::{RunReport, RunStatistics};
+
+const DEFAULT_PLAYER_NAME: &str = "Player";
 
 #[derive(Component)]
 pub struct SummaryView;
+#[derive(Component)]
+pub struct PlayerNameText;
+#[derive(Component)]
+pub struct ExportStatusText;
 #[derive(Component, Default)]
 pub struct RestartButton;
 #[derive(Component, Default)]
+pub struct ReplayButton;
+#[derive(Component, Default)]
+pub struct ExportButton;
+#[derive(Component, Default)]
 pub struct MenuButton;
+#[derive(Component, Default)]
+pub struct SaveNameButton;
+
+/// Present while the player types the name for a score that made it into the high score table.
+#[derive(Resource)]
+pub struct PendingHighScore {
+    name: String,
+    /// The table the score goes to.
+    mode: GameModeKind,
+}
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_summary_view(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     score: Res<Score>,
+    game_rng: Res<GameRng>,
+    last_replay: Option<Res<LastReplay>>,
+    high_scores: Res<HighScores>,
+    game_mode: Res<GameMode>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+    game_over_reason: Option<Res<GameOverReason>>,
+    run_statistics: Res<RunStatistics>,
+    mut received_characters: ResMut<Events<ReceivedCharacter>>,
+)
+{
+    // Watching a replay does not earn the score a second time.
+    let mode = game_mode.get_kind();
+    if replay_playback.is_none() && high_scores.qualifies(mode, score.0) {
+        // Characters typed while playing, like the space that launched the ball, do not belong in the name.
+        received_characters.clear();
+        let name = high_scores.get_last_name().to_string();
+        spawn_name_entry(&mut commands, &asset_server, score.0, &name);
+        commands.insert_resource(PendingHighScore { name, mode });
+        return;
+    }
+
+    let report = get_run_report(&score, &game_rng, game_over_reason, &run_statistics);
+    spawn_summary(&mut commands, &asset_server, &report, last_replay.is_some(), None);
+}
+
+fn get_run_report(
+    score: &Score,
+    game_rng: &GameRng,
+    game_over_reason: Option<Res<GameOverReason>>,
+    run_statistics: &RunStatistics,
+) -> RunReport
+{
+    RunReport {
+        score: score.0,
+        seed: game_rng.get_seed(),
+        reason: game_over_reason.map(|reason| *reason),
+        statistics: run_statistics.clone(),
+    }
+}
+
+fn spawn_summary(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    report: &RunReport,
+    has_replay: bool,
+    rank: Option<usize>,
 )
 {
     commands.spawn(
@@ -23,12 +97,103 @@ pub fn spawn_summary_view(
             get_full_screen_menu_node_bundle(),
         )
     ).with_children(|parent| {
-        spawn_full_screen_menu_header(parent, &asset_server, format!("Your score: {}", score.0));
-        spawn_full_screen_menu_button::<RestartButton>(parent, &asset_server, "Restart", KeyCode::R);
-        spawn_full_screen_menu_button::<MenuButton>(parent, &asset_server, "Menu", KeyCode::M);
+        spawn_full_screen_menu_header(parent, asset_server, format!("Your score: {}", report.score));
+        if let Some(reason) = report.reason {
+            spawn_full_screen_menu_text(parent, asset_server, reason.get_description());
+        }
+        if let Some(rank) = rank {
+            spawn_full_screen_menu_text(parent, asset_server, format!("New high score, rank {}!", rank + 1));
+        }
+        spawn_full_screen_menu_text(parent, asset_server, format!("Seed: {}", report.seed));
+        parent.spawn(
+            TextBundle::from_section(get_statistics_label(&report.statistics), get_small_text_style(asset_server))
+                .with_text_alignment(TextAlignment::Center)
+                .with_style(Style {
+                    max_width: Val::Px(900.),
+                    ..default()
+                })
+        );
+        parent.spawn(
+            (
+                ExportStatusText {},
+                TextBundle::from_section("", get_small_text_style(asset_server)),
+            )
+        );
+        spawn_full_screen_menu_button::<RestartButton>(parent, asset_server, "Restart", RESTART_KEY);
+        if has_replay {
+            spawn_full_screen_menu_button::<ReplayButton>(parent, asset_server, "Replay", REPLAY_KEY);
+        }
+        spawn_full_screen_menu_button::<ExportButton>(parent, asset_server, "Export", EXPORT_KEY);
+        spawn_full_screen_menu_button::<MenuButton>(parent, asset_server, "Menu", MENU_KEY);
     });
 }
 
+fn get_small_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+        font_size: 22.,
+        color: Color::WHITE,
+    }
+}
+
+fn get_statistics_label(statistics: &RunStatistics) -> String {
+    let play_time = statistics.play_time as usize;
+    let collectables = if statistics.collectables_collected.is_empty() {
+        "none".to_string()
+    }
+    else {
+        statistics.collectables_collected
+            .iter()
+            .map(|(name, count)| format!("{} x{}", name, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    format!(
+        "Bricks destroyed: {}    Coins collected: {}\nMost balls at once: {}    Longest rally: {:.1} s    Play time: {}:{:02}\nPicked up: {}",
+        statistics.bricks_destroyed,
+        statistics.coins_collected,
+        statistics.max_simultaneous_balls,
+        statistics.longest_rally,
+        play_time / 60,
+        play_time % 60,
+        collectables,
+    )
+}
+
+fn spawn_name_entry(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    score: usize,
+    name: &str,
+)
+{
+    commands.spawn(
+        (
+            SummaryView {},
+            get_full_screen_menu_node_bundle(),
+        )
+    ).with_children(|parent| {
+        spawn_full_screen_menu_header(parent, asset_server, format!("Your score: {}", score));
+        spawn_full_screen_menu_text(parent, asset_server, "New high score! Enter your name:");
+        parent.spawn(
+            (
+                PlayerNameText {},
+                TextBundle::from_section(get_name_entry_label(name), TextStyle {
+                    font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                    font_size: 40.,
+                    color: Color::BLACK,
+                }),
+            )
+        );
+        spawn_full_screen_menu_button::<SaveNameButton>(parent, asset_server, "Save", Action::Confirm);
+    });
+}
+
+fn get_name_entry_label(name: &str) -> String {
+    format!("{}_", name)
+}
+
 pub fn despawn_summary_view(
     mut commands: Commands,
     view_query: Query<Entity, With<SummaryView>>
@@ -37,13 +202,79 @@ pub fn despawn_summary_view(
     for view in view_query.iter() {
         commands.entity(view).despawn_recursive();
     }
+    commands.remove_resource::<PendingHighScore>();
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn check_name_entry(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    save_button_query: Query<&ReleaseButton, With<SaveNameButton>>,
+    mut name_text_query: Query<&mut Text, With<PlayerNameText>>,
+    view_query: Query<Entity, With<SummaryView>>,
+    mut pending_high_score: ResMut<PendingHighScore>,
+    mut high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    game_rng: Res<GameRng>,
+    last_replay: Option<Res<LastReplay>>,
+    game_over_reason: Option<Res<GameOverReason>>,
+    run_statistics: Res<RunStatistics>,
+)
+{
+    for event in received_characters.read() {
+        let name = &mut pending_high_score.name;
+        let allowed = event.char.is_alphanumeric() || event.char == ' ' || event.char == '-' || event.char == '_';
+        if allowed && name.chars().count() < MAX_PLAYER_NAME_LENGTH {
+            name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(ERASE_NAME_KEY) {
+        pending_high_score.name.pop();
+    }
+
+    for mut text in name_text_query.iter_mut() {
+        text.sections[0].value = get_name_entry_label(&pending_high_score.name);
+    }
+
+    if !save_button_query.iter().any(|button| button.just_released) {
+        return;
+    }
+
+    let name = match pending_high_score.name.trim() {
+        "" => DEFAULT_PLAYER_NAME.to_string(),
+        name => name.to_string(),
+    };
+    let rank = high_scores.insert(pending_high_score.mode, HighScore {
+        name,
+        score: score.0,
+        seed: game_rng.get_seed(),
+    });
+    high_scores.save();
+
+    for view in view_query.iter() {
+        commands.entity(view).despawn_recursive();
+    }
+    commands.remove_resource::<PendingHighScore>();
+    let report = get_run_report(&score, &game_rng, game_over_reason, &run_statistics);
+    spawn_summary(&mut commands, &asset_server, &report, last_replay.is_some(), rank);
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn check_summary_interactions(
     menu_button_query: Query<&ReleaseButton, With<MenuButton>>,
     restart_button_query: Query<&ReleaseButton, With<RestartButton>>,
+    replay_button_query: Query<&ReleaseButton, With<ReplayButton>>,
+    export_button_query: Query<&ReleaseButton, With<ExportButton>>,
+    mut export_status_text_query: Query<&mut Text, With<ExportStatusText>>,
     mut menu_requested_events: EventWriter<MenuRequested>,
     mut restart_requested_events: EventWriter<RestartRequested>,
+    mut replay_requested_events: EventWriter<ReplayRequested>,
+    score: Res<Score>,
+    game_rng: Res<GameRng>,
+    game_over_reason: Option<Res<GameOverReason>>,
+    run_statistics: Res<RunStatistics>,
 )
 {
     for button in menu_button_query.iter() {
@@ -59,4 +290,22 @@ pub fn check_summary_interactions(
             return;
         }
     }
-}
\ No newline at end of file
+
+    for button in replay_button_query.iter() {
+        if button.just_released {
+            replay_requested_events.send_default();
+            return;
+        }
+    }
+
+    if export_button_query.iter().any(|button| button.just_released) {
+        let report = get_run_report(&score, &game_rng, game_over_reason, &run_statistics);
+        let status = match report.export() {
+            Some(path) => format!("Statistics saved to {}", path),
+            None => "The statistics could not be saved".to_string(),
+        };
+        for mut text in export_status_text_query.iter_mut() {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::asset::AssetPath;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Loads sprite textures for gameplay entities.
+///
+/// Without a renderer there is no [`Image`] asset to load, so placeholder handles are handed out instead.
+/// This keeps the game logic usable in headless simulations.
+#[derive(SystemParam)]
+pub struct Textures<'w> {
+    asset_server: Res<'w, AssetServer>,
+    images: Option<Res<'w, Assets<Image>>>,
+}
+
+impl Textures<'_> {
+    pub fn load<'a>(&self, path: impl Into<AssetPath<'a>>) -> Handle<Image> {
+        if self.images.is_some() {
+            self.asset_server.load(path)
+        }
+        else {
+            Handle::default()
+        }
+    }
+}
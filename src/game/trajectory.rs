@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use crate::game::ball::Ball;
+use crate::game::shared::xy0;
+
+/// The longest sequence of bounces recognized as a loop.
+const MAX_LOOP_LENGTH: usize = 4;
+/// How often a sequence has to come back in a row before it counts as a loop.
+const LOOP_REPEATS: usize = 3;
+/// A loop has to repeat within this many seconds, older bounces are forgotten.
+const LOOP_WINDOW_SECONDS: f32 = 30.;
+/// Bounces closer than this are the same bounce.
+const BOUNCE_POSITION_TOLERANCE: f32 = 1.;
+/// The flattest a ball may fly, as the vertical part of its direction. About 11.5 degrees above horizontal.
+const MIN_VERTICAL_DIRECTION: f32 = 0.2;
+/// How much a looping ball is turned to get it out of the loop.
+const LOOP_NUDGE_ANGLE: f32 = 0.17;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallLoopKind {
+    /// The ball flew almost horizontally, it would take ages to come back down.
+    Flat,
+    /// The ball kept bouncing between the same spots without the paddle touching it.
+    RepeatedBounces,
+}
+
+/// Sent whenever the direction of a ball had to be corrected. Normal play should never need it.
+#[derive(Event)]
+pub struct BallLoopDetected {
+    pub ball: Entity,
+    pub kind: BallLoopKind,
+}
+
+/// The recent bounces of a ball, from the latest paddle hit on.
+#[derive(Component, Default)]
+pub struct BallTrajectory {
+    /// When and where the ball bounced, oldest first.
+    bounces: VecDeque<(f32, Vec2)>,
+}
+
+impl BallTrajectory {
+    pub fn record_bounce(&mut self, time: f32, position: Vec2) {
+        if self.bounces.len() == MAX_LOOP_LENGTH * LOOP_REPEATS {
+            self.bounces.pop_front();
+        }
+        self.bounces.push_back((time, position));
+    }
+
+    /// Forgets every bounce, the player is back in control of the ball.
+    pub fn clear(&mut self) {
+        self.bounces.clear();
+    }
+
+    /// Whether the latest bounces are the same few bounces repeated over and over.
+    fn is_looping(&mut self, time: f32) -> bool {
+        while self.bounces.front().is_some_and(|(bounce_time, _)| time - bounce_time > LOOP_WINDOW_SECONDS) {
+            self.bounces.pop_front();
+        }
+
+        let positions: Vec<Vec2> = self.bounces.iter().rev().map(|(_, position)| *position).collect();
+        (1..=MAX_LOOP_LENGTH).any(|loop_length| {
+            positions.len() >= loop_length * LOOP_REPEATS
+                && (0..loop_length * (LOOP_REPEATS - 1)).all(|index| {
+                    positions[index].distance_squared(positions[index + loop_length])
+                        <= BOUNCE_POSITION_TOLERANCE * BOUNCE_POSITION_TOLERANCE
+                })
+        })
+    }
+}
+
+/// Nudges balls out of flat trajectories and endless bounce loops.
+pub fn supervise_ball_trajectories(
+    mut ball_query: Query<(Entity, &mut Ball, &mut BallTrajectory)>,
+    time: Res<Time>,
+    mut ball_loop_detected_events: EventWriter<BallLoopDetected>,
+)
+{
+    let time = time.elapsed_seconds();
+    for (entity, mut ball, mut trajectory) in ball_query.iter_mut() {
+        let direction = ball.direction.xy();
+
+        if direction.y.abs() < MIN_VERTICAL_DIRECTION {
+            // A ball without any vertical movement gets just enough of it, keeping the way it was leaning.
+            let vertical = if direction.y > 0. { MIN_VERTICAL_DIRECTION } else { -MIN_VERTICAL_DIRECTION };
+            let horizontal = (1. - vertical * vertical).sqrt().copysign(direction.x);
+            ball.direction = xy0(Vec2::new(horizontal, vertical));
+            ball_loop_detected_events.send(BallLoopDetected {
+                ball: entity,
+                kind: BallLoopKind::Flat,
+            });
+            continue;
+        }
+
+        if trajectory.is_looping(time) {
+            // Turn mostly horizontal balls towards vertical and mostly vertical ones away from it.
+            let towards_vertical = if direction.x * direction.y >= 0. { 1. } else { -1. };
+            let angle = if direction.y.abs() > direction.x.abs() { -towards_vertical } else { towards_vertical } * LOOP_NUDGE_ANGLE;
+            ball.direction = xy0(Vec2::from_angle(angle).rotate(direction));
+            trajectory.clear();
+            ball_loop_detected_events.send(BallLoopDetected {
+                ball: entity,
+                kind: BallLoopKind::RepeatedBounces,
+            });
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+use bevy::app::AppExit;
+use bevy::core::FrameCount;
+use bevy::input::InputSystem;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use crate::AppState;
+use crate::common::settings::Settings;
+use crate::game::ball::Ball;
+use crate::game::power_ups::StuckBall;
+use crate::game::{GamePlugin, InGameState};
+use crate::game::paddle::Paddle;
+use crate::game::player_input::read_player_input;
+use crate::game::replay::{ReplayPlayback, ReplayRecorder, get_last_replay_path, save_last_replay};
+use crate::game::resources::{GameRng, Lives, Score};
+use crate::game::trajectory::BallLoopDetected;
+use crate::launch_options::LaunchOptions;
+
+pub const HEADLESS_TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+const DEFAULT_NUMBER_OF_FRAMES: u32 = 60 * 60;
+/// How far the paddle may be off the followed ball before the script moves it.
+const PADDLE_FOLLOW_TOLERANCE: f32 = 8.0;
+
+/// Plays a scripted session without a window, then prints the final score and exits.
+///
+/// When a replay is loaded the recorded session is played back instead, otherwise the session is saved as the last replay.
+///
+/// Every frame advances the game by [`HEADLESS_TIMESTEP`], so a session plays out the same way on any machine.
+pub struct HeadlessPlugin {
+    pub frames: Option<u32>,
+}
+
+#[derive(Resource)]
+struct HeadlessSession {
+    frames: u32,
+    started: bool,
+    /// Corrections of the ball supervisor, a scripted session is expected to need none.
+    ball_loops: usize,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_TIMESTEP))
+            .insert_resource(HeadlessSession {
+                frames: self.frames.unwrap_or(DEFAULT_NUMBER_OF_FRAMES),
+                started: false,
+                ball_loops: 0,
+            })
+            .add_systems(Startup, start_session)
+            .add_systems(PreUpdate, play_scripted_input
+                .after(InputSystem)
+                .before(read_player_input)
+                .run_if(not(resource_exists::<ReplayPlayback>()))
+            )
+            .add_systems(Last, check_session_end);
+    }
+}
+
+/// Sets up a headless session without running it, so tests and benchmarks can step through it frame by frame.
+pub fn get_headless_app(launch_options: LaunchOptions, replay_playback: Option<ReplayPlayback>) -> App {
+    // A replay ends on its own, so it is only cut short when asked to.
+    let frames = match replay_playback {
+        Some(_) => launch_options.frames.or(Some(u32::MAX)),
+        None => launch_options.frames,
+    };
+    let mut app = App::new();
+    if let Some(replay_playback) = replay_playback {
+        app.insert_resource(replay_playback);
+    }
+    app
+        .add_state::<AppState>()
+        .insert_resource(launch_options)
+        .add_plugins((
+            MinimalPlugins,
+            LogPlugin::default(),
+            AssetPlugin::default(),
+            bevy::input::InputPlugin,
+        ))
+        .add_plugins((
+            GamePlugin,
+            HeadlessPlugin { frames },
+        ));
+    app
+}
+
+fn start_session(
+    mut next_state: ResMut<NextState<AppState>>,
+)
+{
+    next_state.set(AppState::InGame);
+}
+
+/// Launches the ball, relaunches caught ones and keeps the paddle under the lowest ball, the way a careful player would.
+fn play_scripted_input(
+    mut input: ResMut<Input<KeyCode>>,
+    settings: Res<Settings>,
+    in_game_state: Res<State<InGameState>>,
+    paddle_query: Query<&Transform, With<Paddle>>,
+    ball_query: Query<(&Transform, &Ball)>,
+    stuck_ball_query: Query<(), With<StuckBall>>,
+)
+{
+    let key_bindings = &settings.key_bindings;
+    input.release(key_bindings.launch);
+    input.release(key_bindings.move_left);
+    input.release(key_bindings.move_right);
+
+    match in_game_state.get() {
+        InGameState::Preparation => {
+            input.press(key_bindings.launch);
+        }
+        InGameState::Play => {
+            let Ok(paddle_transform) = paddle_query.get_single() else {
+                return;
+            };
+
+            // Balls caught by a sticky paddle are sent off right away.
+            if !stuck_ball_query.is_empty() {
+                input.press(key_bindings.launch);
+            }
+
+            let lowest_ball = ball_query
+                .iter()
+                .filter(|(_, ball)| ball.direction.y < 0.)
+                .map(|(transform, _)| transform.translation)
+                .min_by(|a, b| a.y.total_cmp(&b.y));
+
+            if let Some(ball_position) = lowest_ball {
+                let offset = ball_position.x - paddle_transform.translation.x;
+                if offset < -PADDLE_FOLLOW_TOLERANCE {
+                    input.press(key_bindings.move_left);
+                }
+                else if offset > PADDLE_FOLLOW_TOLERANCE {
+                    input.press(key_bindings.move_right);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_session_end(
+    mut session: ResMut<HeadlessSession>,
+    frame_count: Res<FrameCount>,
+    app_state: Res<State<AppState>>,
+    in_game_state: Res<State<InGameState>>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+    game_rng: Res<GameRng>,
+    replay_playback: Option<Res<ReplayPlayback>>,
+    mut replay_recorder: ResMut<ReplayRecorder>,
+    mut ball_loop_detected_events: EventReader<BallLoopDetected>,
+    mut app_exit_events: EventWriter<AppExit>,
+)
+{
+    for event in ball_loop_detected_events.read() {
+        eprintln!("Frame {}: ball {:?} needed a {:?} correction.", frame_count.0, event.ball, event.kind);
+        session.ball_loops += 1;
+    }
+
+    let in_game = *app_state.get() == AppState::InGame;
+    if in_game {
+        session.started = true;
+    }
+    else if session.started {
+        eprintln!("The session left the game after {} frames, see the log for details.", frame_count.0);
+        app_exit_events.send(AppExit);
+        return;
+    }
+
+    let replay_finished = replay_playback.is_some_and(|playback| playback.is_finished());
+    if frame_count.0 + 1 >= session.frames || replay_finished || *in_game_state.get() == InGameState::Summary {
+        if let Some(replay) = replay_recorder.finish() {
+            save_last_replay(&replay);
+            if let Some(path) = get_last_replay_path() {
+                println!("Replay: {}", path.display());
+            }
+        }
+        println!("Seed: {}", game_rng.get_seed());
+        println!("Frames: {}", frame_count.0 + 1);
+        println!("Ball loops: {}", session.ball_loops);
+        println!("Lives left: {}", lives.0);
+        println!("Final score: {}", score.0);
+        app_exit_events.send(AppExit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::storage::DATA_DIR_VARIABLE;
+
+    const SEED: u64 = 7;
+    /// Fewer than `DEFAULT_NUMBER_OF_FRAMES`, so the script does not end the session on its own.
+    const FRAMES: u32 = 3000;
+
+    #[test]
+    fn scripted_session_needs_no_ball_loop_corrections() {
+        let launch_options = LaunchOptions {
+            headless: true,
+            seed: Some(SEED),
+            ..default()
+        };
+        // Losing the last ball would save the replay.
+        std::env::set_var(DATA_DIR_VARIABLE, std::env::temp_dir().join(format!("bevy-brick-breaker-test-{}", std::process::id())));
+        let mut app = get_headless_app(launch_options, None);
+        app.finish();
+        app.cleanup();
+        for _ in 0..FRAMES {
+            app.update();
+        }
+
+        assert_eq!(*app.world.resource::<State<AppState>>().get(), AppState::InGame);
+        assert_ne!(*app.world.resource::<State<InGameState>>().get(), InGameState::Summary);
+        assert!(app.world.resource::<Score>().0 > 0);
+        assert_eq!(app.world.resource::<HeadlessSession>().ball_loops, 0);
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+use bevy::prelude::*;
+
+const USAGE: &str = "usage: bevy-brick-breaker [--seed N] [--replay FILE] [--headless [--frames N]]";
+
+/// Options passed on the command line.
+#[derive(Resource, Clone, Default)]
+pub struct LaunchOptions {
+    /// Runs a scripted session without a window and prints the final score.
+    pub headless: bool,
+    /// How many frames the headless session lasts at most.
+    pub frames: Option<u32>,
+    /// Seeds every run, takes precedence over the seed of the level.
+    pub seed: Option<u64>,
+    /// Plays back a recorded session instead of starting a new one.
+    pub replay: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => {
+                    options.headless = true;
+                }
+                "--frames" => {
+                    let value = args.next().ok_or_else(|| format!("--frames expects a value\n{USAGE}"))?;
+                    let frames = value.parse().map_err(|_| format!("invalid number of frames: {value}\n{USAGE}"))?;
+                    options.frames = Some(frames);
+                }
+                "--seed" => {
+                    let value = args.next().ok_or_else(|| format!("--seed expects a value\n{USAGE}"))?;
+                    let seed = value.parse().map_err(|_| format!("invalid seed: {value}\n{USAGE}"))?;
+                    options.seed = Some(seed);
+                }
+                "--replay" => {
+                    let value = args.next().ok_or_else(|| format!("--replay expects a file\n{USAGE}"))?;
+                    options.replay = Some(PathBuf::from(value));
+                }
+                _ => {
+                    return Err(format!("unknown argument: {arg}\n{USAGE}"));
+                }
+            }
+        }
+
+        if options.frames.is_some() && !options.headless {
+            return Err(format!("--frames can only be used with --headless\n{USAGE}"));
+        }
+
+        if options.replay.is_some() && options.seed.is_some() {
+            return Err(format!("--seed cannot be used with --replay, the replay brings its own seed\n{USAGE}"));
+        }
+
+        Ok(options)
+    }
+}
@@ -0,0 +1,22 @@
+pub mod game;
+pub mod menu;
+pub mod common;
+pub mod headless;
+pub mod launch_options;
+
+use bevy::prelude::*;
+
+pub const WINDOW_WORLD_HEIGHT: f32 = 720.0;
+pub const WINDOW_USABLE_WORLD_WIDTH: f32 = 1280.0;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum AppState {
+    #[default]
+    Menu,
+    InGame,
+    RestartInGame,
+    HighScores,
+    Options,
+    ModeSelect,
+    StageSelect,
+}
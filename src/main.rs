@@ -1,45 +1,88 @@
-mod game;
-mod menu;
-mod common;
-
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
 use bevy::text::TextSettings;
 use bevy::window::WindowResized;
-use crate::common::better_button::BetterButtonPlugin;
-use crate::game::GamePlugin;
-use crate::menu::MenuPlugin;
+use bevy_brick_breaker::{AppState, WINDOW_USABLE_WORLD_WIDTH, WINDOW_WORLD_HEIGHT};
+use bevy_brick_breaker::common::better_button::BetterButtonPlugin;
+use bevy_brick_breaker::common::settings::{Settings, apply_settings};
+use bevy_brick_breaker::game::{GamePlugin, GameViewPlugin};
+use bevy_brick_breaker::game::replay::{Replay, ReplayPlayback};
+use bevy_brick_breaker::headless::get_headless_app;
+use bevy_brick_breaker::launch_options::LaunchOptions;
+use bevy_brick_breaker::menu::MenuPlugin;
 
-pub const WINDOW_WORLD_HEIGHT: f32 = 720.0;
-pub const WINDOW_USABLE_WORLD_WIDTH: f32 = 1280.0;
 const MIN_WINDOW_WIDTH_TO_HEIGHT: f32 = WINDOW_USABLE_WORLD_WIDTH / WINDOW_WORLD_HEIGHT;
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-pub enum AppState {
-    #[default]
-    Menu,
-    InGame,
-    RestartInGame,
+fn main() {
+    let launch_options = match LaunchOptions::from_args() {
+        Ok(launch_options) => launch_options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    let replay_playback = launch_options.replay.as_ref().map(|path| {
+        match Replay::load(path) {
+            Ok(replay) => ReplayPlayback::new(replay),
+            Err(error) => {
+                eprintln!("could not load the replay '{}': {}", path.display(), error);
+                std::process::exit(1);
+            }
+        }
+    });
+
+    if launch_options.headless {
+        run_headless(launch_options, replay_playback);
+    }
+    else {
+        run_windowed(launch_options, replay_playback);
+    }
 }
 
-fn main() {
-    App::new()
+fn run_windowed(launch_options: LaunchOptions, replay_playback: Option<ReplayPlayback>) {
+    // Loaded before anything else, so the window opens the way it was left.
+    let settings = Settings::load();
+    let window = Window {
+        mode: settings.get_window_mode(),
+        present_mode: settings.get_present_mode(),
+        ..default()
+    };
+
+    let mut app = App::new();
+    if let Some(replay_playback) = replay_playback {
+        app.insert_resource(replay_playback);
+    }
+    app
         .add_state::<AppState>()
-        .add_plugins(DefaultPlugins)
+        .insert_resource(launch_options)
+        .insert_resource(settings)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(window),
+            ..default()
+        }))
         .insert_resource(TextSettings {
             allow_dynamic_font_size: true,
             ..default()
         })
         .add_plugins((
             MenuPlugin,
-            GamePlugin, 
+            GamePlugin,
+            GameViewPlugin,
             BetterButtonPlugin,
         ))
         .add_systems(Startup, spawn_camera)
-        .add_systems(Update, guard_resolution)
+        .add_systems(Update, (
+            guard_resolution,
+            apply_settings.run_if(resource_changed::<Settings>()),
+        ))
         .run();
 }
 
+fn run_headless(launch_options: LaunchOptions, replay_playback: Option<ReplayPlayback>) {
+    get_headless_app(launch_options, replay_playback).run();
+}
+
 fn spawn_camera(
     mut commands: Commands,
     window_query: Query<&Window>
@@ -65,6 +108,7 @@ fn guard_resolution(
     mut window_query: Query<&mut Window>,
     mut resize_reader: EventReader<WindowResized>,
     mut ui_scale: ResMut<UiScale>,
+    settings: Res<Settings>,
 )
 {
     let mut last_window_resized_event = None;
@@ -77,6 +121,6 @@ fn guard_resolution(
             let mut window = window_query.get_single_mut().unwrap();
             window.resolution.set(MIN_WINDOW_WIDTH_TO_HEIGHT * e.height, e.height);
         }
-        ui_scale.0 = (e.height / WINDOW_WORLD_HEIGHT) as f64;
+        ui_scale.0 = settings.get_ui_scale(e.height);
     }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use crate::AppState;
+use crate::common::actions::Action;
+use crate::common::better_button::ReleaseButton;
+use crate::common::shortcuts::HIGH_SCORES_MODE_KEY;
+use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header, spawn_full_screen_menu_text, spawn_full_screen_menu_wide_button};
+use crate::game::game_mode::{GameMode, GameModeKind};
+use crate::game::high_scores::HighScores;
+
+#[derive(Component)]
+pub struct HighScoresView;
+/// Shows the table of the next game mode.
+#[derive(Component)]
+pub struct ModeButton;
+#[derive(Component, Default)]
+pub struct BackButton;
+
+/// Present while the high scores are shown.
+#[derive(Resource)]
+pub struct HighScoresState {
+    /// The game mode whose table is shown.
+    mode: GameModeKind,
+}
+
+/// Starts with the table of the mode played last.
+pub fn start_high_scores(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+) {
+    commands.insert_resource(HighScoresState {
+        mode: game_mode.get_kind(),
+    });
+}
+
+/// Rebuilds the screen, so it shows the table of the current mode.
+pub fn update_high_scores_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+    high_scores_state: Res<HighScoresState>,
+    root_query: Query<Entity, With<HighScoresView>>
+) {
+    if let Ok(root) = root_query.get_single() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let entries = high_scores.get_entries(high_scores_state.mode);
+    commands.spawn((
+        HighScoresView {},
+        get_full_screen_menu_node_bundle(),
+    )).with_children(|builder| {
+        spawn_full_screen_menu_header(builder, &asset_server, "High Scores");
+        spawn_full_screen_menu_wide_button(builder, &asset_server, ModeButton {}, format!("Mode: {}", high_scores_state.mode.get_name()), HIGH_SCORES_MODE_KEY);
+        if entries.is_empty() {
+            spawn_full_screen_menu_text(builder, &asset_server, "No high scores yet");
+        }
+        for (rank, entry) in entries.iter().enumerate() {
+            spawn_full_screen_menu_text(builder, &asset_server, format!("{}. {} - {}", rank + 1, entry.name, entry.score));
+        }
+        spawn_full_screen_menu_button::<BackButton>(builder, &asset_server, "Back", Action::Back);
+    });
+}
+
+pub fn despawn_high_scores_ui(
+    mut commands: Commands,
+    root_query: Query<Entity, With<HighScoresView>>
+) {
+    commands.remove_resource::<HighScoresState>();
+    if let Ok(root) = root_query.get_single() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+pub fn check_high_scores_interactions(
+    mode_button_query: Query<&ReleaseButton, With<ModeButton>>,
+    back_button_query: Query<&ReleaseButton, With<BackButton>>,
+    mut high_scores_state: ResMut<HighScoresState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for button in mode_button_query.iter() {
+        if button.just_released {
+            high_scores_state.mode = high_scores_state.mode.get_next();
+            return;
+        }
+    }
+
+    for button in back_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::Menu);
+            return;
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use bevy::app::AppExit;
 use bevy::prelude::*;
 use crate::AppState;
+use crate::common::actions::Action;
 use crate::common::better_button::ReleaseButton;
+use crate::common::shortcuts::{HIGH_SCORES_KEY, OPTIONS_KEY, QUIT_KEY};
 use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button};
 
 #[derive(Component)]
@@ -9,6 +11,10 @@ pub struct MenuView;
 #[derive(Component, Default)]
 pub struct PlayButton;
 #[derive(Component, Default)]
+pub struct HighScoresButton;
+#[derive(Component, Default)]
+pub struct OptionsButton;
+#[derive(Component, Default)]
 pub struct QuitButton;
 
 pub fn spawn_menu_ui(
@@ -19,8 +25,10 @@ pub fn spawn_menu_ui(
         MenuView {},
         get_full_screen_menu_node_bundle(),
     )).with_children(|builder| {
-        spawn_full_screen_menu_button::<PlayButton>(builder, &asset_server, "Play", KeyCode::P);
-        spawn_full_screen_menu_button::<QuitButton>(builder, &asset_server, "Quit", KeyCode::Q);
+        spawn_full_screen_menu_button::<PlayButton>(builder, &asset_server, "Play", Action::Confirm);
+        spawn_full_screen_menu_button::<HighScoresButton>(builder, &asset_server, "High Scores", HIGH_SCORES_KEY);
+        spawn_full_screen_menu_button::<OptionsButton>(builder, &asset_server, "Options", OPTIONS_KEY);
+        spawn_full_screen_menu_button::<QuitButton>(builder, &asset_server, "Quit", QUIT_KEY);
     });
 }
 
@@ -35,13 +43,29 @@ pub fn despawn_menu_ui(
 
 pub fn check_menu_interactions(
     play_button_query: Query<&ReleaseButton, With<PlayButton>>,
+    high_scores_button_query: Query<&ReleaseButton, With<HighScoresButton>>,
+    options_button_query: Query<&ReleaseButton, With<OptionsButton>>,
     quit_button_query: Query<&ReleaseButton, With<QuitButton>>,
     mut next_state: ResMut<NextState<AppState>>,
     mut app_exit_event_writer: EventWriter<AppExit>,
 ) {
     for button in play_button_query.iter() {
         if button.just_released {
-            next_state.set(AppState::InGame);
+            next_state.set(AppState::ModeSelect);
+            return;
+        }
+    }
+
+    for button in high_scores_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::HighScores);
+            return;
+        }
+    }
+
+    for button in options_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::Options);
             return;
         }
     }
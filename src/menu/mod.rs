@@ -1,8 +1,17 @@
 mod menu_view;
+mod high_scores_view;
+mod options_view;
+mod stage_select_view;
+mod mode_select_view;
 
 use bevy::prelude::*;
 use crate::AppState;
+use crate::menu::high_scores_view::{HighScoresState, check_high_scores_interactions, despawn_high_scores_ui, start_high_scores, update_high_scores_ui};
+use crate::menu::options_view::{OptionsState, check_options_interactions, finish_options, start_options, update_options_ui};
+use crate::common::settings::Settings;
 use crate::menu::menu_view::{check_menu_interactions, despawn_menu_ui, spawn_menu_ui};
+use crate::menu::mode_select_view::{check_mode_select_interactions, despawn_mode_select_ui, spawn_mode_select_ui};
+use crate::menu::stage_select_view::{check_stage_select_interactions, despawn_stage_select_ui, spawn_stage_select_ui};
 
 pub struct MenuPlugin;
 
@@ -12,6 +21,27 @@ impl Plugin for MenuPlugin {
             .add_systems(OnEnter(AppState::Menu), spawn_menu_ui)
             .add_systems(Update, (check_menu_interactions,)
                 .run_if(in_state(AppState::Menu)))
-            .add_systems(OnExit(AppState::Menu), despawn_menu_ui);
+            .add_systems(OnExit(AppState::Menu), despawn_menu_ui)
+            .add_systems(OnEnter(AppState::HighScores), start_high_scores)
+            .add_systems(Update, (
+                check_high_scores_interactions,
+                update_high_scores_ui.run_if(resource_exists_and_changed::<HighScoresState>()),
+            ).chain().run_if(in_state(AppState::HighScores)))
+            .add_systems(OnExit(AppState::HighScores), despawn_high_scores_ui)
+            .add_systems(OnEnter(AppState::ModeSelect), spawn_mode_select_ui)
+            .add_systems(Update, (check_mode_select_interactions,)
+                .run_if(in_state(AppState::ModeSelect)))
+            .add_systems(OnExit(AppState::ModeSelect), despawn_mode_select_ui)
+            .add_systems(OnEnter(AppState::StageSelect), spawn_stage_select_ui)
+            .add_systems(Update, (check_stage_select_interactions,)
+                .run_if(in_state(AppState::StageSelect)))
+            .add_systems(OnExit(AppState::StageSelect), despawn_stage_select_ui)
+            .add_systems(OnEnter(AppState::Options), start_options)
+            .add_systems(Update, (
+                check_options_interactions,
+                update_options_ui.run_if(resource_exists_and_changed::<OptionsState>()
+                    .or_else(resource_changed::<Settings>())),
+            ).chain().run_if(in_state(AppState::Options)))
+            .add_systems(OnExit(AppState::Options), finish_options);
     }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use crate::AppState;
+use crate::common::actions::Action;
+use crate::common::better_button::ReleaseButton;
+use crate::common::shortcuts::{ADVANCING_WALL_KEY, CAMPAIGN_KEY, TIME_ATTACK_KEY};
+use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header, spawn_full_screen_menu_wide_button};
+use crate::game::game_mode::{GameMode, TIME_ATTACK_SECONDS};
+
+#[derive(Component)]
+pub struct ModeSelectView;
+#[derive(Component, Default)]
+pub struct EndlessButton;
+#[derive(Component, Default)]
+pub struct TimeAttackButton;
+#[derive(Component, Default)]
+pub struct AdvancingWallButton;
+#[derive(Component, Default)]
+pub struct CampaignButton;
+#[derive(Component, Default)]
+pub struct BackButton;
+
+pub fn spawn_mode_select_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        ModeSelectView {},
+        get_full_screen_menu_node_bundle(),
+    )).with_children(|builder| {
+        spawn_full_screen_menu_header(builder, &asset_server, "Play");
+        spawn_full_screen_menu_wide_button(builder, &asset_server, EndlessButton {}, "Endless", Action::Confirm);
+        let time_attack_label = format!("Time Attack ({} min)", (TIME_ATTACK_SECONDS / 60.).round());
+        spawn_full_screen_menu_wide_button(builder, &asset_server, TimeAttackButton {}, time_attack_label, TIME_ATTACK_KEY);
+        spawn_full_screen_menu_wide_button(builder, &asset_server, AdvancingWallButton {}, "Advancing Wall", ADVANCING_WALL_KEY);
+        spawn_full_screen_menu_wide_button(builder, &asset_server, CampaignButton {}, "Campaign", CAMPAIGN_KEY);
+        spawn_full_screen_menu_button::<BackButton>(builder, &asset_server, "Back", Action::Back);
+    });
+}
+
+pub fn despawn_mode_select_ui(
+    mut commands: Commands,
+    root_query: Query<Entity, With<ModeSelectView>>
+) {
+    if let Ok(root) = root_query.get_single() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+pub fn check_mode_select_interactions(
+    endless_button_query: Query<&ReleaseButton, With<EndlessButton>>,
+    time_attack_button_query: Query<&ReleaseButton, With<TimeAttackButton>>,
+    advancing_wall_button_query: Query<&ReleaseButton, With<AdvancingWallButton>>,
+    campaign_button_query: Query<&ReleaseButton, With<CampaignButton>>,
+    back_button_query: Query<&ReleaseButton, With<BackButton>>,
+    mut game_mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for button in endless_button_query.iter() {
+        if button.just_released {
+            *game_mode = GameMode::Endless;
+            next_state.set(AppState::InGame);
+            return;
+        }
+    }
+
+    for button in time_attack_button_query.iter() {
+        if button.just_released {
+            *game_mode = GameMode::TimeAttack;
+            next_state.set(AppState::InGame);
+            return;
+        }
+    }
+
+    for button in advancing_wall_button_query.iter() {
+        if button.just_released {
+            *game_mode = GameMode::AdvancingWall;
+            next_state.set(AppState::InGame);
+            return;
+        }
+    }
+
+    // The campaign stage is picked in the stage select.
+    for button in campaign_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::StageSelect);
+            return;
+        }
+    }
+
+    for button in back_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::Menu);
+            return;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use crate::AppState;
+use crate::common::better_button::ReleaseButton;
+use crate::common::actions::{Action, KeyBindings, get_key_name, is_reserved_key};
+use crate::common::settings::Settings;
+use crate::common::shortcuts::{BINDING_BUTTON_KEYS, CONTROL_SCHEME_KEY, FULLSCREEN_KEY, UI_SCALE_KEY, VOLUME_KEY, VSYNC_KEY};
+use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header, spawn_full_screen_menu_wide_button};
+
+#[derive(Component)]
+pub struct OptionsView;
+#[derive(Component)]
+pub struct FullscreenButton;
+#[derive(Component)]
+pub struct VsyncButton;
+#[derive(Component)]
+pub struct UiScaleButton;
+#[derive(Component)]
+pub struct VolumeButton;
+#[derive(Component)]
+pub struct ControlSchemeButton;
+#[derive(Component)]
+pub struct BindingButton(Action);
+#[derive(Component, Default)]
+pub struct BackButton;
+
+/// Present while the options screen is shown.
+#[derive(Resource, Default)]
+pub struct OptionsState {
+    /// The action waiting for the player to press its new key.
+    awaiting_binding: Option<Action>,
+    /// The last key refused for the awaited action because a button uses it.
+    rejected_key: Option<KeyCode>,
+}
+
+pub fn start_options(
+    mut commands: Commands,
+) {
+    commands.init_resource::<OptionsState>();
+}
+
+pub fn finish_options(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    root_query: Query<Entity, With<OptionsView>>
+) {
+    settings.save();
+    commands.remove_resource::<OptionsState>();
+    if let Ok(root) = root_query.get_single() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+/// Rebuilds the screen, so every button shows the current value.
+pub fn update_options_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    options_state: Res<OptionsState>,
+    root_query: Query<Entity, With<OptionsView>>
+) {
+    if let Ok(root) = root_query.get_single() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let on_off = |value: bool| if value { "On" } else { "Off" };
+    let ui_scale = match settings.ui_scale {
+        Some(scale) => format!("{}%", (scale * 100.).round()),
+        None => "Auto".to_string(),
+    };
+    let binding = |action: Action| match (options_state.awaiting_binding, options_state.rejected_key) {
+        (Some(awaiting_action), Some(key_code)) if awaiting_action == action => format!("{}: {} is taken, press another key", action.get_name(), get_key_name(key_code)),
+        (Some(awaiting_action), None) if awaiting_action == action => format!("{}: press a key, Esc cancels", action.get_name()),
+        _ => format!("{}: {}", action.get_name(), get_key_name(settings.key_bindings.get(action))),
+    };
+
+    commands.spawn((
+        OptionsView {},
+        get_full_screen_menu_node_bundle(),
+    )).with_children(|builder| {
+        spawn_full_screen_menu_header(builder, &asset_server, "Options");
+        spawn_full_screen_menu_wide_button(builder, &asset_server, FullscreenButton {}, format!("Fullscreen: {}", on_off(settings.fullscreen)), FULLSCREEN_KEY);
+        spawn_full_screen_menu_wide_button(builder, &asset_server, VsyncButton {}, format!("VSync: {}", on_off(settings.vsync)), VSYNC_KEY);
+        spawn_full_screen_menu_wide_button(builder, &asset_server, UiScaleButton {}, format!("UI scale: {}", ui_scale), UI_SCALE_KEY);
+        spawn_full_screen_menu_wide_button(builder, &asset_server, VolumeButton {}, format!("Volume: {}%", (settings.volume * 100.).round()), VOLUME_KEY);
+        spawn_full_screen_menu_wide_button(builder, &asset_server, ControlSchemeButton {}, format!("Controls: {}", settings.control_scheme.get_name()), CONTROL_SCHEME_KEY);
+        for (action, key_code) in Action::ALL.into_iter().zip(BINDING_BUTTON_KEYS) {
+            spawn_full_screen_menu_wide_button(builder, &asset_server, BindingButton(action), binding(action), key_code);
+        }
+        spawn_full_screen_menu_button::<BackButton>(builder, &asset_server, "Back", Action::Back);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn check_options_interactions(
+    fullscreen_button_query: Query<&ReleaseButton, With<FullscreenButton>>,
+    vsync_button_query: Query<&ReleaseButton, With<VsyncButton>>,
+    ui_scale_button_query: Query<&ReleaseButton, With<UiScaleButton>>,
+    volume_button_query: Query<&ReleaseButton, With<VolumeButton>>,
+    control_scheme_button_query: Query<&ReleaseButton, With<ControlSchemeButton>>,
+    binding_button_query: Query<(&ReleaseButton, &BindingButton)>,
+    back_button_query: Query<&ReleaseButton, With<BackButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut options_state: ResMut<OptionsState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // While a key is being bound, every key goes to the binding, none of them presses a button.
+    if let Some(action) = options_state.awaiting_binding {
+        if let Some(key_code) = keyboard_input.get_just_pressed().next().copied() {
+            // Escape cancels, except for the actions it is the default of, so they can get it back.
+            if key_code == KeyCode::Escape && KeyBindings::default().get(action) != KeyCode::Escape {
+                options_state.awaiting_binding = None;
+                options_state.rejected_key = None;
+            }
+            else if is_reserved_key(action, key_code) {
+                options_state.rejected_key = Some(key_code);
+            }
+            else {
+                settings.key_bindings.set(action, key_code);
+                options_state.awaiting_binding = None;
+                options_state.rejected_key = None;
+            }
+        }
+        return;
+    }
+
+    for button in fullscreen_button_query.iter() {
+        if button.just_released {
+            settings.fullscreen = !settings.fullscreen;
+            return;
+        }
+    }
+
+    for button in vsync_button_query.iter() {
+        if button.just_released {
+            settings.vsync = !settings.vsync;
+            return;
+        }
+    }
+
+    for button in ui_scale_button_query.iter() {
+        if button.just_released {
+            settings.cycle_ui_scale();
+            return;
+        }
+    }
+
+    for button in volume_button_query.iter() {
+        if button.just_released {
+            settings.cycle_volume();
+            return;
+        }
+    }
+
+    for button in control_scheme_button_query.iter() {
+        if button.just_released {
+            settings.toggle_control_scheme();
+            return;
+        }
+    }
+
+    for (button, binding_button) in binding_button_query.iter() {
+        if button.just_released {
+            options_state.awaiting_binding = Some(binding_button.0);
+            return;
+        }
+    }
+
+    for button in back_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::Menu);
+            return;
+        }
+    }
+}
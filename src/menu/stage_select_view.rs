@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use crate::AppState;
+use crate::common::actions::Action;
+use crate::common::better_button::ReleaseButton;
+use crate::common::shortcuts::STAGE_BUTTON_KEYS;
+use crate::common::styles::{get_full_screen_menu_node_bundle, spawn_full_screen_menu_button, spawn_full_screen_menu_header, spawn_full_screen_menu_text, spawn_full_screen_menu_wide_button};
+use crate::game::campaign::{CAMPAIGN_STAGE_PATHS, CampaignProgress};
+use crate::game::game_mode::GameMode;
+
+#[derive(Component)]
+pub struct StageSelectView;
+/// Starts the campaign at the given stage.
+#[derive(Component)]
+pub struct StageButton(usize);
+#[derive(Component, Default)]
+pub struct BackButton;
+
+pub fn spawn_stage_select_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    campaign_progress: Res<CampaignProgress>,
+) {
+    commands.spawn((
+        StageSelectView {},
+        get_full_screen_menu_node_bundle(),
+    )).with_children(|builder| {
+        spawn_full_screen_menu_header(builder, &asset_server, "Campaign");
+        for (stage, key_code) in (0..CAMPAIGN_STAGE_PATHS.len()).zip(STAGE_BUTTON_KEYS) {
+            if campaign_progress.is_unlocked(stage) {
+                spawn_full_screen_menu_wide_button(builder, &asset_server, StageButton(stage), format!("Stage {}", stage + 1), key_code);
+            }
+            else {
+                spawn_full_screen_menu_text(builder, &asset_server, format!("Stage {} (locked)", stage + 1));
+            }
+        }
+        spawn_full_screen_menu_button::<BackButton>(builder, &asset_server, "Back", Action::Back);
+    });
+}
+
+pub fn despawn_stage_select_ui(
+    mut commands: Commands,
+    root_query: Query<Entity, With<StageSelectView>>
+) {
+    if let Ok(root) = root_query.get_single() {
+        commands.entity(root).despawn_recursive();
+    }
+}
+
+pub fn check_stage_select_interactions(
+    stage_button_query: Query<(&ReleaseButton, &StageButton)>,
+    back_button_query: Query<&ReleaseButton, With<BackButton>>,
+    mut game_mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (button, stage_button) in stage_button_query.iter() {
+        if button.just_released {
+            *game_mode = GameMode::Campaign {
+                stage: stage_button.0,
+                carry_over: None,
+            };
+            next_state.set(AppState::InGame);
+            return;
+        }
+    }
+
+    for button in back_button_query.iter() {
+        if button.just_released {
+            next_state.set(AppState::ModeSelect);
+            return;
+        }
+    }
+}
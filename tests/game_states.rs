@@ -0,0 +1,144 @@
+use bevy::input::ButtonState;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_brick_breaker::AppState;
+use bevy_brick_breaker::common::storage::DATA_DIR_VARIABLE;
+use bevy_brick_breaker::game::{GamePlugin, InGameState};
+use bevy_brick_breaker::game::ball::Ball;
+use bevy_brick_breaker::game::events::{LastBallDestroyed, MenuRequested, RestartRequested};
+use bevy_brick_breaker::game::paddle::Paddle;
+use bevy_brick_breaker::game::resources::Score;
+use bevy_brick_breaker::headless::HEADLESS_TIMESTEP;
+
+/// How many frames a state change may take, loading the level included.
+const MAX_FRAMES: usize = 1000;
+
+fn get_app() -> App {
+    // Keeps the replays and high scores of finished games out of the player's data directory.
+    std::env::set_var(DATA_DIR_VARIABLE, std::env::temp_dir().join(format!("bevy-brick-breaker-test-{}", std::process::id())));
+    let mut app = App::new();
+    app
+        .add_state::<AppState>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_TIMESTEP))
+        .add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            bevy::input::InputPlugin,
+            GamePlugin,
+        ));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn get_in_game_state(app: &App) -> InGameState {
+    *app.world.resource::<State<InGameState>>().get()
+}
+
+fn get_app_state(app: &App) -> AppState {
+    *app.world.resource::<State<AppState>>().get()
+}
+
+fn update_until(app: &mut App, in_game_state: InGameState) {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        if get_in_game_state(app) == in_game_state {
+            return;
+        }
+        // Gives the asset loader a moment to finish reading the level.
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("still in {:?} instead of {:?}", get_in_game_state(app), in_game_state);
+}
+
+fn press(app: &mut App, key_code: KeyCode) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+}
+
+fn get_prepared_app() -> App {
+    let mut app = get_app();
+    app.world.resource_mut::<NextState<AppState>>().set(AppState::InGame);
+    update_until(&mut app, InGameState::Preparation);
+    app
+}
+
+fn count<T: Component>(app: &mut App) -> usize {
+    app.world.query_filtered::<(), With<T>>().iter(&app.world).count()
+}
+
+#[test]
+fn loads_the_level_before_the_preparation() {
+    let mut app = get_prepared_app();
+
+    assert_eq!(count::<Paddle>(&mut app), 1);
+    assert_eq!(count::<Ball>(&mut app), 1);
+}
+
+#[test]
+fn starts_playing_on_a_key_press() {
+    let mut app = get_prepared_app();
+
+    press(&mut app, KeyCode::Space);
+
+    assert_eq!(get_in_game_state(&app), InGameState::Play);
+}
+
+#[test]
+fn pauses_and_resumes_with_escape() {
+    let mut app = get_prepared_app();
+    press(&mut app, KeyCode::Space);
+
+    press(&mut app, KeyCode::Escape);
+    assert_eq!(get_in_game_state(&app), InGameState::Pause);
+
+    press(&mut app, KeyCode::Escape);
+    assert_eq!(get_in_game_state(&app), InGameState::Play);
+}
+
+#[test]
+fn shows_the_summary_after_the_last_ball() {
+    let mut app = get_prepared_app();
+    press(&mut app, KeyCode::Space);
+
+    app.world.send_event(LastBallDestroyed);
+    update_until(&mut app, InGameState::Summary);
+
+    assert_eq!(get_app_state(&app), AppState::InGame);
+}
+
+#[test]
+fn restarts_with_a_fresh_level() {
+    let mut app = get_prepared_app();
+    press(&mut app, KeyCode::Space);
+    app.world.resource_mut::<Score>().0 = 1000;
+
+    app.world.send_event(RestartRequested);
+    update_until(&mut app, InGameState::Preparation);
+
+    assert_eq!(get_app_state(&app), AppState::InGame);
+    assert_eq!(app.world.resource::<Score>().0, 0);
+    assert_eq!(count::<Paddle>(&mut app), 1);
+    assert_eq!(count::<Ball>(&mut app), 1);
+}
+
+#[test]
+fn leaves_nothing_behind_in_the_menu() {
+    let mut app = get_prepared_app();
+    press(&mut app, KeyCode::Space);
+
+    app.world.send_event(MenuRequested);
+    update_until(&mut app, InGameState::None);
+
+    assert_eq!(get_app_state(&app), AppState::Menu);
+    assert_eq!(count::<Paddle>(&mut app), 0);
+    assert_eq!(count::<Ball>(&mut app), 0);
+}